@@ -0,0 +1,130 @@
+#!/usr/bin/env python3
+"""Generate the glyph atlases of the non-proportional VT fonts.
+
+The glyphs are taken from the public domain X11 "misc-fixed" bitmap fonts by Markus Kuhn et al.
+(https://www.cl.cam.ac.uk/~mgk25/ucs-fonts.html), which are for example also shipped in the
+fonts/src directory of the embedded-graphics crate. Pass the directory with the BDF files:
+
+    python3 generate_atlases.py path/to/bdf/fonts
+
+For every ISO 8859 code page of the VT font types and every bitmap size, a 1-bit grayscale PNG is
+written with the 256 characters of the code page in a 16 by 16 grid, white on black. Every VT font
+size is an integer multiple of one of the bitmap sizes, see `VtFont::bitmap_size`.
+"""
+
+import os
+import re
+import struct
+import sys
+import zlib
+
+CODE_PAGES = {
+    "latin1": "iso8859_1",
+    "latin9": "iso8859_15",
+    "latin2": "iso8859_2",
+    "latin4": "iso8859_4",
+    "cyrillic": "iso8859_5",
+    "greek": "iso8859_7",
+}
+
+# Bitmap size (width, height) and the BDF font drawn in it
+BITMAP_SIZES = {
+    (6, 8): "5x8.bdf",
+    (8, 8): "5x8.bdf",
+    (8, 12): "6x12.bdf",
+    (12, 16): "9x15.bdf",
+}
+
+
+def read_bdf(path):
+    """Read a character cell BDF font, returns the cell size, the ascent and the glyphs by code
+    point as (x offset, y offset, width, height, rows of hex digits)"""
+    with open(path, encoding="latin-1") as file:
+        text = file.read()
+    width, height, _, _ = map(int, re.search(r"^FONTBOUNDINGBOX (.*)$", text, re.M).group(1).split())
+    ascent = int(re.search(r"^FONT_ASCENT (\d+)$", text, re.M).group(1))
+    glyphs = {}
+    for char in re.finditer(r"^STARTCHAR.*?^ENDCHAR", text, re.M | re.S):
+        block = char.group(0)
+        encoding = int(re.search(r"^ENCODING (-?\d+)", block, re.M).group(1))
+        bbx = list(map(int, re.search(r"^BBX (.*)$", block, re.M).group(1).split()))
+        rows = block.split("BITMAP\n", 1)[1].split("\n")[: bbx[1]]
+        glyphs[encoding] = (bbx[2], bbx[3], bbx[0], bbx[1], rows)
+    return (width, height), ascent, glyphs
+
+
+def draw_glyph(pixels, stride, origin, font, glyph):
+    (font_width, _), ascent, _ = font
+    x_offset, y_offset, width, height, rows = glyph
+    for row, hex_row in enumerate(rows):
+        bits = int(hex_row, 16)
+        bit_count = len(hex_row) * 4
+        for column in range(width):
+            if bits >> (bit_count - 1 - column) & 1:
+                x = x_offset + column
+                y = ascent - y_offset - height + row
+                if 0 <= x < font_width:
+                    pixels[(origin[1] + y) * stride + origin[0] + x] = 1
+
+
+def write_png(path, width, height, pixels):
+    raw = bytearray()
+    for y in range(height):
+        raw.append(0)
+        row = pixels[y * width : (y + 1) * width]
+        for x in range(0, width, 8):
+            byte = 0
+            for bit, value in enumerate(row[x : x + 8]):
+                byte |= value << (7 - bit)
+            raw.append(byte)
+
+    def chunk(kind, data):
+        return (
+            struct.pack(">I", len(data))
+            + kind
+            + data
+            + struct.pack(">I", zlib.crc32(kind + data) & 0xFFFFFFFF)
+        )
+
+    with open(path, "wb") as file:
+        file.write(b"\x89PNG\r\n\x1a\n")
+        file.write(chunk(b"IHDR", struct.pack(">IIBBBBB", width, height, 1, 0, 0, 0, 0)))
+        file.write(chunk(b"IDAT", zlib.compress(bytes(raw), 9)))
+        file.write(chunk(b"IEND", b""))
+
+
+def main():
+    font_directory = sys.argv[1]
+    output_directory = os.path.dirname(os.path.abspath(__file__))
+    for (cell_width, cell_height), font_file in BITMAP_SIZES.items():
+        font = read_bdf(os.path.join(font_directory, font_file))
+        (font_width, font_height), _, glyphs = font
+        # The font cell is centred in the bitmap cell
+        margin = ((cell_width - font_width) // 2, (cell_height - font_height) // 2)
+        for name, codec in CODE_PAGES.items():
+            width, height = cell_width * 16, cell_height * 16
+            pixels = bytearray(width * height)
+            for byte in list(range(0x20, 0x7F)) + list(range(0xA0, 0x100)):
+                try:
+                    char = bytes([byte]).decode(codec)
+                except UnicodeDecodeError:
+                    continue
+                glyph = glyphs.get(ord(char))
+                if glyph is None:
+                    print(f"{font_file} has no glyph for {char!r} of {name}", file=sys.stderr)
+                    continue
+                origin = (
+                    byte % 16 * cell_width + margin[0],
+                    byte // 16 * cell_height + margin[1],
+                )
+                draw_glyph(pixels, width, origin, font, glyph)
+            write_png(
+                os.path.join(output_directory, f"{name}_{cell_width}x{cell_height}.png"),
+                width,
+                height,
+                pixels,
+            )
+
+
+if __name__ == "__main__":
+    main()
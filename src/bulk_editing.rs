@@ -0,0 +1,346 @@
+//! Copyright 2024 - The Open-Agriculture Developers
+//! SPDX-License-Identifier: GPL-3.0-or-later
+//! Authors: Daan Steenbergen
+
+use ag_iso_stack::object_pool::object::Object;
+
+/// Get the names of the listed attributes that differ between `before` and `after`, and copy them
+/// to `target` if it is an object of the same type
+macro_rules! compare {
+    ($before:expr, $after:expr, $target:expr, $variant:ident, $($field:ident),+ $(,)?) => {{
+        let mut target = match $target {
+            Some(Object::$variant(target)) => Some(target),
+            _ => None,
+        };
+        let mut changed = Vec::new();
+        $(
+            if $before.$field != $after.$field {
+                changed.push(stringify!($field).replace('_', " "));
+                if let Some(target) = target.as_mut() {
+                    target.$field = $after.$field.clone();
+                }
+            }
+        )+
+        changed
+    }};
+}
+
+/// Apply the changes made to an object, from `before` to `after`, to another object of the same
+/// type. Only the attributes that objects of the same type commonly share are applied, like
+/// colours, sizes, options and font, line and fill attributes. The id, values, variables, child
+/// objects and macros of the target are left as they are.
+pub fn apply_attribute_changes(before: &Object, after: &Object, target: &mut Object) {
+    compare_attributes(before, after, Some(target));
+}
+
+/// Get the names of the attributes that differ between two versions of an object, e.g.
+/// "font colour". Only the attributes that `apply_attribute_changes` applies are named.
+pub fn changed_attributes(before: &Object, after: &Object) -> Vec<String> {
+    compare_attributes(before, after, None)
+}
+
+fn compare_attributes(before: &Object, after: &Object, target: Option<&mut Object>) -> Vec<String> {
+    match (before, after) {
+        (Object::WorkingSet(b), Object::WorkingSet(a)) => {
+            compare!(b, a, target, WorkingSet, background_colour, selectable)
+        }
+        (Object::DataMask(b), Object::DataMask(a)) => {
+            compare!(b, a, target, DataMask, background_colour)
+        }
+        (Object::AlarmMask(b), Object::AlarmMask(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                AlarmMask,
+                background_colour,
+                priority,
+                acoustic_signal
+            )
+        }
+        (Object::Container(b), Object::Container(a)) => {
+            compare!(b, a, target, Container, width, height, hidden)
+        }
+        (Object::SoftKeyMask(b), Object::SoftKeyMask(a)) => {
+            compare!(b, a, target, SoftKeyMask, background_colour)
+        }
+        (Object::Key(b), Object::Key(a)) => {
+            compare!(b, a, target, Key, background_colour)
+        }
+        (Object::Button(b), Object::Button(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                Button,
+                width,
+                height,
+                background_colour,
+                border_colour,
+                options
+            )
+        }
+        (Object::InputBoolean(b), Object::InputBoolean(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                InputBoolean,
+                background_colour,
+                width,
+                foreground_colour,
+                enabled
+            )
+        }
+        (Object::InputString(b), Object::InputString(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                InputString,
+                width,
+                height,
+                background_colour,
+                font_attributes,
+                input_attributes,
+                options,
+                justification,
+                enabled,
+            )
+        }
+        (Object::InputNumber(b), Object::InputNumber(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                InputNumber,
+                width,
+                height,
+                background_colour,
+                font_attributes,
+                options,
+                min_value,
+                max_value,
+                offset,
+                scale,
+                nr_of_decimals,
+                format,
+                justification,
+                options2,
+            )
+        }
+        (Object::InputList(b), Object::InputList(a)) => {
+            compare!(b, a, target, InputList, width, height, options)
+        }
+        (Object::OutputString(b), Object::OutputString(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputString,
+                width,
+                height,
+                background_colour,
+                font_attributes,
+                options,
+                justification,
+            )
+        }
+        (Object::OutputNumber(b), Object::OutputNumber(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputNumber,
+                width,
+                height,
+                background_colour,
+                font_attributes,
+                options,
+                offset,
+                scale,
+                nr_of_decimals,
+                format,
+                justification,
+            )
+        }
+        (Object::OutputList(b), Object::OutputList(a)) => {
+            compare!(b, a, target, OutputList, width, height)
+        }
+        (Object::OutputLine(b), Object::OutputLine(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputLine,
+                line_attributes,
+                width,
+                height,
+                line_direction
+            )
+        }
+        (Object::OutputRectangle(b), Object::OutputRectangle(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputRectangle,
+                line_attributes,
+                width,
+                height,
+                line_suppression,
+                fill_attributes,
+            )
+        }
+        (Object::OutputEllipse(b), Object::OutputEllipse(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputEllipse,
+                line_attributes,
+                width,
+                height,
+                ellipse_type,
+                start_angle,
+                end_angle,
+                fill_attributes,
+            )
+        }
+        // The size of a polygon is not copied, as its points are relative to it
+        (Object::OutputPolygon(b), Object::OutputPolygon(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputPolygon,
+                line_attributes,
+                fill_attributes,
+                polygon_type
+            )
+        }
+        (Object::OutputMeter(b), Object::OutputMeter(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputMeter,
+                width,
+                needle_colour,
+                border_colour,
+                arc_and_tick_colour,
+                options,
+                nr_of_ticks,
+                start_angle,
+                end_angle,
+                min_value,
+                max_value,
+            )
+        }
+        (Object::OutputLinearBarGraph(b), Object::OutputLinearBarGraph(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputLinearBarGraph,
+                width,
+                height,
+                colour,
+                target_line_colour,
+                options,
+                nr_of_ticks,
+                min_value,
+                max_value,
+            )
+        }
+        (Object::OutputArchedBarGraph(b), Object::OutputArchedBarGraph(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                OutputArchedBarGraph,
+                width,
+                height,
+                colour,
+                target_line_colour,
+                options,
+                start_angle,
+                end_angle,
+                bar_graph_width,
+                min_value,
+                max_value,
+            )
+        }
+        (Object::PictureGraphic(b), Object::PictureGraphic(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                PictureGraphic,
+                width,
+                options,
+                transparency_colour
+            )
+        }
+        (Object::FontAttributes(b), Object::FontAttributes(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                FontAttributes,
+                font_colour,
+                font_size,
+                font_type,
+                font_style
+            )
+        }
+        (Object::LineAttributes(b), Object::LineAttributes(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                LineAttributes,
+                line_colour,
+                line_width,
+                line_art
+            )
+        }
+        (Object::FillAttributes(b), Object::FillAttributes(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                FillAttributes,
+                fill_type,
+                fill_colour,
+                fill_pattern
+            )
+        }
+        (Object::Animation(b), Object::Animation(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                Animation,
+                width,
+                height,
+                refresh_interval,
+                enabled,
+                options
+            )
+        }
+        (Object::ScaledGraphic(b), Object::ScaledGraphic(a)) => {
+            compare!(
+                b,
+                a,
+                target,
+                ScaledGraphic,
+                width,
+                height,
+                scale_type,
+                options
+            )
+        }
+        _ => Vec::new(),
+    }
+}
@@ -2,34 +2,112 @@
 //! SPDX-License-Identifier: GPL-3.0-or-later
 //! Authors: Daan Steenbergen
 
-use std::{cell::RefCell, collections::HashMap};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
-use ag_iso_stack::object_pool::{object::Object, NullableObjectId, ObjectId, ObjectPool, ObjectType};
+use ag_iso_stack::object_pool::{
+    object::Object, object_attributes::Point, vt_version::VtVersion, NullableObjectId, ObjectId,
+    ObjectPool, ObjectRef, ObjectType,
+};
 
-use crate::{ObjectInfo, smart_naming, project_file::ProjectFile};
+use crate::{
+    allowed_object_relationships::get_allowed_child_refs,
+    bulk_editing,
+    object_clipboard::{self, ClipboardContent, ClipboardRoot},
+    object_references::{self, ReferenceMut},
+    object_sizing,
+    project_file::{GridSettings, ProjectFile, ProjectSettings, SoftKeyPlacement},
+    smart_naming,
+    undo_history::{HistoryEntry, UndoHistory},
+    ObjectInfo,
+};
 
-const MAX_UNDO_REDO_POOL: usize = 10;
 const MAX_UNDO_REDO_SELECTED: usize = 20;
 
+/// The last revision given to a project, revisions are unique across all projects
+static LAST_REVISION: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    LAST_REVISION.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// How far a duplicate is moved from the original, so it doesn't hide it
+const DUPLICATE_OFFSET: i16 = 10;
+
+/// Minimum size of a soft key designator according to the standard
+const MIN_SOFT_KEY_WIDTH: u16 = 60;
+const MIN_SOFT_KEY_HEIGHT: u16 = 32;
+
+/// The VT versions a project can be designed for, indexed by their version number
+pub const VT_VERSIONS: [VtVersion; 7] = [
+    VtVersion::Version0,
+    VtVersion::Version1,
+    VtVersion::Version2,
+    VtVersion::Version3,
+    VtVersion::Version4,
+    VtVersion::Version5,
+    VtVersion::Version6,
+];
+
 #[derive(Default, Clone)]
 pub struct EditorProject {
     pool: ObjectPool,
     mut_pool: RefCell<ObjectPool>,
-    undo_pool_history: Vec<ObjectPool>,
-    redo_pool_history: Vec<ObjectPool>,
+    /// Objects changed in the mutating pool, which are added to the history in the next frame
+    changed_objects: RefCell<HashSet<ObjectId>>,
+    /// Whether the objects in the mutating pool were reordered
+    order_changed: bool,
+    /// The object infos of changed objects as they were before the change
+    changed_object_infos: RefCell<HashMap<ObjectId, Option<ObjectInfo>>>,
+    /// Labels of the actions that changed the mutating pool, shown in the history
+    action_labels: RefCell<Vec<String>>,
+    history: UndoHistory,
+    /// Incremented on every change to the pool or the object names, including undo and redo
+    revision: u64,
     selected_object: NullableObjectId,
     mut_selected_object: RefCell<NullableObjectId>,
     undo_selected_history: Vec<NullableObjectId>,
     redo_selected_history: Vec<NullableObjectId>,
+    /// Objects selected together with the selected object, using shift or ctrl
+    extra_selected: Vec<ObjectId>,
+    /// New extra selected objects that are applied in the next frame
+    mut_extra_selected: RefCell<Option<Vec<ObjectId>>>,
     pub mask_size: u16,
     soft_key_size: (u16, u16),
+    /// Number of physical soft keys shown next to the mask
+    pub soft_key_count: u8,
+    /// Where the soft keys are shown relative to the mask
+    pub soft_key_placement: SoftKeyPlacement,
+    /// Version number of the VT the object pool is designed for, see `VT_VERSIONS`
+    pub vt_version: u8,
+    /// Grid and snapping settings of the mask preview
+    pub grid: GridSettings,
     pub object_info: RefCell<HashMap<ObjectId, ObjectInfo>>,
 
+    /// For every referenced object, the objects referencing it, once for every reference
+    reference_index: HashMap<ObjectId, Vec<ObjectId>>,
+
     /// Used to keep track of the object that is being renamed
     renaming_object: RefCell<Option<(eframe::egui::Id, ObjectId, String)>>,
+
+    /// Used to keep track of the objects that are being deleted, and whether their children are too
+    deleting_objects: RefCell<Option<(Vec<ObjectId>, bool)>>,
+
+    /// Object ID change that is applied to the whole pool in the next frame
+    pending_id_change: RefCell<Option<(ObjectId, ObjectId)>>,
+
+    /// Problems of pasting or duplicating objects that are not reported to the user yet
+    clipboard_problems: RefCell<Vec<String>>,
     
     /// Cached next available ID for efficient allocation
     next_available_id: RefCell<u16>,
+
+    /// IDs allocated for objects that may not be in the mutating pool yet
+    allocated_ids: RefCell<HashSet<ObjectId>>,
     
     /// Cached default object names for efficient lookup
     default_object_names: RefCell<HashMap<ObjectId, String>>,
@@ -38,6 +116,13 @@ pub struct EditorProject {
 impl From<ObjectPool> for EditorProject {
     fn from(pool: ObjectPool) -> Self {
         let (mask_size, soft_key_size) = pool.get_minimum_mask_sizes();
+        let settings = ProjectSettings::default();
+
+        // Make sure there is room to preview the soft keys, even if the pool doesn't have any yet
+        let soft_key_size = (
+            soft_key_size.0.max(MIN_SOFT_KEY_WIDTH),
+            soft_key_size.1.max(MIN_SOFT_KEY_HEIGHT),
+        );
         
         // Find the highest ID in use to initialize next_available_id
         let max_id = pool.objects()
@@ -46,20 +131,37 @@ impl From<ObjectPool> for EditorProject {
             .max()
             .unwrap_or(0);
         
+        let reference_index = build_reference_index(&pool);
+
         EditorProject {
             mut_pool: RefCell::new(pool.clone()),
             pool,
-            undo_pool_history: Default::default(),
-            redo_pool_history: Default::default(),
+            changed_objects: RefCell::new(HashSet::new()),
+            order_changed: false,
+            changed_object_infos: RefCell::new(HashMap::new()),
+            action_labels: RefCell::new(Vec::new()),
+            history: UndoHistory::default(),
+            revision: next_revision(),
             selected_object: NullableObjectId::default(),
             mut_selected_object: RefCell::new(NullableObjectId::default()),
             undo_selected_history: Default::default(),
             redo_selected_history: Default::default(),
+            extra_selected: Default::default(),
+            mut_extra_selected: RefCell::new(None),
             mask_size,
             soft_key_size,
+            soft_key_count: settings.soft_key_count,
+            soft_key_placement: settings.soft_key_placement,
+            vt_version: settings.vt_version,
+            grid: settings.grid,
             object_info: RefCell::new(HashMap::new()),
+            reference_index,
             renaming_object: RefCell::new(None),
+            deleting_objects: RefCell::new(None),
+            pending_id_change: RefCell::new(None),
+            clipboard_problems: RefCell::new(Vec::new()),
             next_available_id: RefCell::new(max_id.saturating_add(1)),
+            allocated_ids: RefCell::new(HashSet::new()),
             default_object_names: RefCell::new(HashMap::new()),
         }
     }
@@ -73,35 +175,46 @@ impl EditorProject {
     
     /// Allocate a new unique object ID efficiently
     pub fn allocate_object_id(&self) -> ObjectId {
+        self.allocate_object_id_in(0..=u16::MAX - 1)
+            .expect("No available ObjectId: all IDs from 0 to 65534 are taken.")
+    }
+
+    /// Allocate a new unique ID for a Macro object. Objects reference their macros with a single
+    /// byte, so IDs below 256 are preferred. If they are all taken and `byte_referenced` is
+    /// false, an ID in the range of the VT version is used instead, see
+    /// `object_references::max_macro_id`. Returns None if no ID is free.
+    pub fn allocate_macro_id(&self, byte_referenced: bool) -> Option<ObjectId> {
+        let max_id = object_references::max_macro_id(self.get_vt_version());
+        self.allocate_object_id_in(0..=u8::MAX as u16).or_else(|| {
+            (!byte_referenced && max_id > u8::MAX as u16)
+                .then(|| self.allocate_object_id_in(u8::MAX as u16 + 1..=max_id))
+                .flatten()
+        })
+    }
+
+    /// Allocate an ID in `range` that isn't used in the mutating pool and wasn't allocated since
+    /// the last update of the pool, so allocating several IDs before adding the objects is safe.
+    /// The search starts at the cached next available ID if it is in the range.
+    fn allocate_object_id_in(&self, range: RangeInclusive<u16>) -> Option<ObjectId> {
+        let pool = self.mut_pool.borrow();
+        let mut allocated_ids = self.allocated_ids.borrow_mut();
         let mut next_id = self.next_available_id.borrow_mut();
-        
-        // Find the next available ID starting from our cached value
-        while self.pool.object_by_id(ObjectId::new(*next_id).unwrap_or_default()).is_some() {
-            *next_id = next_id.saturating_add(1);
-            
-            // Handle wraparound at u16::MAX
-            if *next_id == 0 {
-                // If we've wrapped around, do a full scan to find any gaps
-                let mut found = false;
-                for id in 1..=u16::MAX {
-                    if self.pool.object_by_id(ObjectId::new(id).unwrap_or_default()).is_none() {
-                        *next_id = id;
-                        found = true;
-                        break;
-                    }
-                }
-                if !found {
-                    panic!("No available ObjectId: all IDs from 1 to u16::MAX are taken.");
-                }
-                break;
-            }
+        let from_cache = range.contains(&*next_id);
+        let start = if from_cache { *next_id } else { *range.start() };
+
+        // Find the next available ID, wrapping around to find any gaps
+        let allocated_id = (start..=*range.end())
+            .chain(*range.start()..start)
+            .filter_map(|id| ObjectId::new(id).ok())
+            .find(|id| pool.object_by_id(*id).is_none() && !allocated_ids.contains(id))?;
+
+        allocated_ids.insert(allocated_id);
+        if from_cache {
+            *next_id = allocated_id.value().saturating_add(1);
         }
-        
-        let allocated_id = ObjectId::new(*next_id).unwrap_or_default();
-        *next_id = next_id.saturating_add(1);
-        allocated_id
+        Some(allocated_id)
     }
-    
+
     /// Update the next available ID cache based on the current pool
     fn update_next_available_id(&self) {
         let max_id = self.pool.objects()
@@ -112,16 +225,175 @@ impl EditorProject {
         self.next_available_id.replace(max_id.saturating_add(1));
     }
 
+    /// Get the size of a soft key designator
+    pub fn get_soft_key_size(&self) -> (u16, u16) {
+        self.soft_key_size
+    }
+
+    /// Get the VT version the object pool is designed for
+    pub fn get_vt_version(&self) -> VtVersion {
+        VT_VERSIONS
+            .get(self.vt_version as usize)
+            .copied()
+            .unwrap_or(VtVersion::Version3)
+    }
+
     /// Get the current selected object
     pub fn get_selected(&self) -> NullableObjectId {
         self.selected_object
     }
 
-    /// Get the current mutating object pool
+    /// Get all selected objects, starting with the selected object
+    pub fn get_selection(&self) -> Vec<ObjectId> {
+        self.selected_object
+            .0
+            .into_iter()
+            .chain(self.extra_selected.iter().copied())
+            .collect()
+    }
+
+    /// Check if an object is part of the current selection
+    pub fn is_selected(&self, object_id: ObjectId) -> bool {
+        self.selected_object.0 == Some(object_id) || self.extra_selected.contains(&object_id)
+    }
+
+    /// Select an object in the next frame. With ctrl (cmd on mac) the object is added to or
+    /// removed from the selection, with shift it is added to the selection. Otherwise only this
+    /// object is selected. An added object becomes the selected object shown in the parameters.
+    pub fn select_object(&self, object_id: ObjectId, modifiers: eframe::egui::Modifiers) {
+        let mut selection = self.get_mut_selection();
+
+        let was_selected = selection.contains(&object_id);
+        selection.retain(|id| *id != object_id);
+        if !modifiers.command && !modifiers.shift {
+            selection.clear();
+        }
+        // When deselecting, the next object in the selection becomes the selected object
+        if !(modifiers.command && was_selected) {
+            selection.insert(0, object_id);
+        }
+
+        self.set_mut_selection(selection);
+    }
+
+    /// Get all objects that are selected in the next frame, starting with the selected object
+    fn get_mut_selection(&self) -> Vec<ObjectId> {
+        let extra_selected = self
+            .mut_extra_selected
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| self.extra_selected.clone());
+        self.mut_selected_object
+            .borrow()
+            .0
+            .into_iter()
+            .chain(extra_selected)
+            .collect()
+    }
+
+    /// Set the objects that are selected in the next frame, the first becomes the selected object
+    fn set_mut_selection(&self, selection: Vec<ObjectId>) {
+        self.mut_selected_object
+            .replace(selection.first().copied().into());
+        self.mut_extra_selected
+            .replace(Some(selection.into_iter().skip(1).collect()));
+    }
+
+    /// Apply the attribute changes made to an object to the other selected objects of the same
+    /// type, see `bulk_editing::apply_attribute_changes`
+    pub fn apply_to_selection(&self, before: &Object, after: &Object) {
+        let selection = self.get_selection();
+        let attributes = bulk_editing::changed_attributes(before, after);
+        if selection.len() > 1 && !attributes.is_empty() {
+            self.label_action(format!(
+                "Change {} of {}",
+                attributes.join(", "),
+                count_objects(selection.len())
+            ));
+        }
+
+        let mut pool = self.mut_pool.borrow_mut();
+        for object_id in selection {
+            if object_id == after.id() {
+                continue;
+            }
+            if let Some(target) = pool.object_mut_by_id(object_id) {
+                bulk_editing::apply_attribute_changes(before, after, target);
+                self.object_changed(object_id);
+            }
+        }
+    }
+
+    /// Edit an object in the mutating pool
     /// This is used to make changes to the pool in the next frame
     /// without affecting the current pool
-    pub fn get_mut_pool(&self) -> &RefCell<ObjectPool> {
-        &self.mut_pool
+    /// Returns None if the object doesn't exist
+    pub fn edit_object<R>(
+        &self,
+        object_id: ObjectId,
+        edit: impl FnOnce(&mut Object) -> R,
+    ) -> Option<R> {
+        let mut pool = self.mut_pool.borrow_mut();
+        let object = pool.object_mut_by_id(object_id)?;
+        let before = object.clone();
+        let result = edit(object);
+        if *object != before {
+            self.object_changed(object_id);
+        }
+        Some(result)
+    }
+
+    /// Add a new object to the mutating pool
+    pub fn add_object(&self, object: Object) {
+        self.object_changed(object.id());
+        self.mut_pool.borrow_mut().add(object);
+    }
+
+    /// Mark an object as changed in the mutating pool, so the change is added to the history
+    fn object_changed(&self, object_id: ObjectId) {
+        self.changed_objects.borrow_mut().insert(object_id);
+    }
+
+    /// Remember the info of an object before it is changed, so the change can be undone
+    fn object_info_changing(&self, object_id: ObjectId) {
+        let before = self.object_info.borrow().get(&object_id).cloned();
+        self.changed_object_infos
+            .borrow_mut()
+            .entry(object_id)
+            .or_insert(before);
+    }
+
+    /// Label the action that changes the mutating pool in this frame, which is shown in the
+    /// history. Actions without a label are described by their changes.
+    fn label_action(&self, label: String) {
+        self.action_labels.borrow_mut().push(label);
+    }
+
+    /// Get the name of an object in the current pool, for labels in the history
+    fn object_name(&self, object_id: ObjectId) -> String {
+        match self.pool.object_by_id(object_id) {
+            Some(object) => self.get_object_info(object).get_name(object),
+            None => format!("object {}", object_id.value()),
+        }
+    }
+
+    /// Describe objects in the current pool for labels in the history, by name if it is a single
+    /// object
+    fn objects_label(&self, object_ids: &[ObjectId]) -> String {
+        match object_ids {
+            [object_id] => self.object_name(*object_id),
+            _ => count_objects(object_ids.len()),
+        }
+    }
+
+    /// Set the name of an object, this can be undone
+    pub fn set_object_name(&self, object: &Object, name: String) {
+        self.object_info_changing(object.id());
+        self.object_info
+            .borrow_mut()
+            .entry(object.id())
+            .or_insert_with(|| ObjectInfo::new(object))
+            .set_name(name);
     }
 
     /// Set the mutating selected object
@@ -131,72 +403,168 @@ impl EditorProject {
         &self.mut_selected_object
     }
 
-    /// If the mutating pool is different from the current pool, add the current pool to the history
-    /// and update the current pool with the mutated pool.
+    /// Apply the changes made to the mutating pool and the object infos to the current pool,
+    /// and add them to the history as a single step. Only the changed objects are compared.
     /// Returns true if the pool was updated
     pub fn update_pool(&mut self) -> bool {
-        if self.mut_pool.borrow().to_owned() != self.pool {
-            self.redo_pool_history.clear();
-            self.undo_pool_history.push(self.pool.clone());
-            if self.undo_pool_history.len() > MAX_UNDO_REDO_POOL {
-                self.undo_pool_history
-                    .drain(..self.undo_pool_history.len() - MAX_UNDO_REDO_POOL);
-            }
-            self.pool = self.mut_pool.borrow().clone();
-            // Clear the default names cache since objects may have changed
-            self.default_object_names.borrow_mut().clear();
-            return true;
+        if let Some((old_id, new_id)) = self.pending_id_change.take() {
+            self.apply_object_id_change(old_id, new_id);
         }
-        false
+        self.allocated_ids.get_mut().clear();
+        let changed_objects = std::mem::take(self.changed_objects.get_mut());
+        let changed_object_infos = std::mem::take(self.changed_object_infos.get_mut());
+        let order_changed = std::mem::take(&mut self.order_changed);
+        let action_labels = std::mem::take(self.action_labels.get_mut());
+        if changed_objects.is_empty() && changed_object_infos.is_empty() && !order_changed {
+            return false;
+        }
+
+        let mut entry = HistoryEntry::default();
+        let mut_pool = self.mut_pool.get_mut();
+        for object_id in changed_objects {
+            entry.record_object(
+                object_id,
+                self.pool.object_by_id(object_id).cloned(),
+                mut_pool.object_by_id(object_id).cloned(),
+            );
+        }
+        if order_changed || entry.adds_or_removes_objects() {
+            entry.record_order(object_order(&self.pool), object_order(mut_pool));
+        }
+        let object_info = self.object_info.get_mut();
+        for (object_id, before) in changed_object_infos {
+            entry.record_info(object_id, before, object_info.get(&object_id).cloned());
+        }
+        if entry.is_empty() {
+            return false;
+        }
+        let label = combine_labels(action_labels).unwrap_or_else(|| {
+            entry.describe(|object| match object_info.get(&object.id()) {
+                Some(info) => info.get_name(object),
+                None => ObjectInfo::new(object).get_name(object),
+            })
+        });
+        entry.set_label(label);
+
+        entry.apply_objects(&mut self.pool, true);
+        self.history.push(entry);
+        self.pool_changed();
+        true
+    }
+
+    /// Update the caches that depend on the current pool after it changed
+    fn pool_changed(&mut self) {
+        self.revision = next_revision();
+        self.reference_index = build_reference_index(&self.pool);
+        // Clear the default names cache since objects may have changed
+        self.default_object_names.borrow_mut().clear();
+    }
+
+    /// Get the revision of the project, which changes whenever the project is changed. This is
+    /// used to detect changes that are not saved yet. No other project has the same revision, so
+    /// it also identifies the pool for caches of the rendered objects.
+    pub fn get_revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Set the maximum number of actions that can be undone, None for unlimited
+    pub fn set_undo_depth(&mut self, depth: Option<usize>) {
+        self.history.set_max_depth(depth);
     }
 
     /// Undo the last action
     pub fn undo(&mut self) {
-        if let Some(pool) = self.undo_pool_history.pop() {
-            self.redo_pool_history.push(self.pool.clone());
-
-            // Both need to be replaced here because otherwise it will be added to the undo history
-            self.pool = pool.clone();
-            self.mut_pool.replace(pool);
-            
-            // Update next_available_id based on the new pool state
-            self.update_next_available_id();
-            
-            // Clear the default names cache since objects may have changed
-            self.default_object_names.borrow_mut().clear();
+        if self.undo_step() {
+            self.history_applied();
         }
     }
 
     /// Check if there are actions available to undo
     pub fn undo_available(&self) -> bool {
-        !self.undo_pool_history.is_empty()
+        self.history.can_undo()
     }
 
     /// Redo the last undone action
     pub fn redo(&mut self) {
-        if let Some(pool) = self.redo_pool_history.pop() {
-            self.undo_pool_history.push(self.pool.clone());
-            // Both need to be replaced here because otherwise the redo history will be cleared
-            self.pool = pool.clone();
-            self.mut_pool.replace(pool);
-            
-            // Update next_available_id based on the new pool state
-            self.update_next_available_id();
-            
-            // Clear the default names cache since objects may have changed
-            self.default_object_names.borrow_mut().clear();
+        if self.redo_step() {
+            self.history_applied();
+        }
+    }
+
+    /// Undo or redo actions until the given number of actions in the history is applied, see
+    /// `get_history_labels`
+    pub fn jump_to_history(&mut self, position: usize) {
+        let mut changed = false;
+        while self.history.position() > position && self.undo_step() {
+            changed = true;
+        }
+        while self.history.position() < position && self.redo_step() {
+            changed = true;
         }
+        if changed {
+            self.history_applied();
+        }
+    }
+
+    /// Get the labels of the actions that can be undone, oldest first, and of the actions that
+    /// can be redone, next first. The number of actions that can be undone is the position in
+    /// the history.
+    pub fn get_history_labels(&self) -> (Vec<String>, Vec<String>) {
+        (
+            self.history
+                .undo_entries()
+                .map(|entry| entry.label().to_string())
+                .collect(),
+            self.history
+                .redo_entries()
+                .map(|entry| entry.label().to_string())
+                .collect(),
+        )
+    }
+
+    /// Undo the last action without updating the caches, returns false if there is none
+    fn undo_step(&mut self) -> bool {
+        let Some(entry) = self.history.undo() else {
+            return false;
+        };
+        // Both need to be changed here because otherwise it will be added to the undo history
+        entry.apply_objects(&mut self.pool, false);
+        entry.apply_objects(self.mut_pool.get_mut(), false);
+        entry.apply_infos(self.object_info.get_mut(), false);
+        true
+    }
+
+    /// Redo the last undone action without updating the caches, returns false if there is none
+    fn redo_step(&mut self) -> bool {
+        let Some(entry) = self.history.redo() else {
+            return false;
+        };
+        // Both need to be changed here because otherwise the redo history will be cleared
+        entry.apply_objects(&mut self.pool, true);
+        entry.apply_objects(self.mut_pool.get_mut(), true);
+        entry.apply_infos(self.object_info.get_mut(), true);
+        true
+    }
+
+    /// Update the caches after undoing or redoing actions
+    fn history_applied(&mut self) {
+        self.pool_changed();
+
+        // Update next_available_id based on the new pool state
+        self.update_next_available_id();
     }
 
     /// Check if there are actions available to redo
     pub fn redo_available(&self) -> bool {
-        !self.redo_pool_history.is_empty()
+        self.history.can_redo()
     }
 
     /// Update the selected object with the mutating selected object if it is different
     /// Returns true if the selected object was updated
     pub fn update_selected(&mut self) -> bool {
         let mut_selected = self.mut_selected_object.borrow().to_owned();
+        let mut mut_extra_selected = self.mut_extra_selected.take();
+        let mut updated = false;
         if mut_selected != self.selected_object {
             self.redo_selected_history.clear();
             if mut_selected != NullableObjectId::NULL {
@@ -207,9 +575,17 @@ impl EditorProject {
                 }
             }
             self.selected_object = mut_selected;
-            return true;
+            // Selecting another object without shift or ctrl ends the multi-selection
+            mut_extra_selected.get_or_insert_with(Vec::new);
+            updated = true;
+        }
+        if let Some(extra_selected) = mut_extra_selected {
+            if extra_selected != self.extra_selected {
+                self.extra_selected = extra_selected;
+                updated = true;
+            }
         }
-        false
+        updated
     }
 
     /// Set the selected object to the previous object in the history
@@ -219,6 +595,7 @@ impl EditorProject {
             // Both need to be replaced here because otherwise it will be added to the undo history
             self.selected_object = selected.clone();
             self.mut_selected_object.replace(selected);
+            self.extra_selected.clear();
         }
     }
 
@@ -229,11 +606,99 @@ impl EditorProject {
             // Both need to be replaced here because otherwise the redo history will be cleared
             self.selected_object = selected.clone();
             self.mut_selected_object.replace(selected);
+            self.extra_selected.clear();
+        }
+    }
+
+    /// Count the references to an object from all objects in the current pool
+    pub fn count_references_to(&self, id: ObjectId) -> usize {
+        self.reference_index.get(&id).map_or(0, Vec::len)
+    }
+
+    /// Change the id of an object in the next frame, together with all references to it.
+    /// The change is applied to the mutating pool so it can be undone in a single step.
+    pub fn change_object_id(&self, old_id: ObjectId, new_id: ObjectId) {
+        self.pending_id_change.replace(Some((old_id, new_id)));
+    }
+
+    fn apply_object_id_change(&mut self, old_id: ObjectId, new_id: ObjectId) {
+        let mut pool = self.mut_pool.borrow_mut();
+        if pool.object_by_id(new_id).is_some() {
+            return;
+        }
+        match pool.object_mut_by_id(old_id) {
+            Some(object) => *object.mut_id() = new_id,
+            None => return,
+        }
+        self.label_action(format!(
+            "Change ID of {} to {}",
+            self.object_name(old_id),
+            new_id.value()
+        ));
+        self.object_changed(old_id);
+        self.object_changed(new_id);
+        for object in pool.objects_mut() {
+            if object_references::replace_references(object, old_id, new_id) > 0 {
+                self.object_changed(object.id());
+            }
+        }
+        drop(pool);
+
+        self.update_object_id_for_info(old_id, new_id);
+        self.default_object_names.borrow_mut().remove(&old_id);
+
+        // Keep the selection and its history pointing at the same object
+        let replace_selected = |selected: &mut NullableObjectId| {
+            if selected.0 == Some(old_id) {
+                *selected = new_id.into();
+            }
+        };
+        replace_selected(self.mut_selected_object.get_mut());
+        self.undo_selected_history
+            .iter_mut()
+            .for_each(replace_selected);
+        self.redo_selected_history
+            .iter_mut()
+            .for_each(replace_selected);
+        for extra_selected in self
+            .extra_selected
+            .iter_mut()
+            .chain(self.mut_extra_selected.get_mut().iter_mut().flatten())
+        {
+            if *extra_selected == old_id {
+                *extra_selected = new_id;
+            }
+        }
+    }
+
+    /// Move a child object to a new offset in the object references of its parent.
+    /// The index is used to tell apart multiple references to the same child.
+    pub fn move_object_ref(&self, parent_id: ObjectId, index: usize, offset: Point<i16>) {
+        let mut pool = self.mut_pool.borrow_mut();
+        let object_ref = pool
+            .object_mut_by_id(parent_id)
+            .and_then(object_references::object_refs_mut)
+            .and_then(|object_refs| object_refs.get_mut(index));
+        if let Some(object_ref) = object_ref {
+            object_ref.offset = offset;
+            self.label_action(format!("Move {}", self.object_name(object_ref.id)));
+            self.object_changed(parent_id);
+        }
+    }
+
+    /// Change the width and height attributes of an object, see `object_sizing::set_object_size`
+    pub fn resize_object(&self, object_id: ObjectId, width: u16, height: u16) {
+        if let Some(object) = self.mut_pool.borrow_mut().object_mut_by_id(object_id) {
+            object_sizing::set_object_size(object, width, height);
+            self.label_action(format!("Resize {}", self.object_name(object_id)));
+            self.object_changed(object_id);
         }
     }
 
     /// Change an object id in the object info hashmap
     pub fn update_object_id_for_info(&self, old_id: ObjectId, new_id: ObjectId) {
+        self.object_info_changing(old_id);
+        self.object_info_changing(new_id);
         let mut object_info = self.object_info.borrow_mut();
         if let Some(info) = object_info.remove(&old_id) {
             object_info.insert(new_id, info);
@@ -265,21 +730,435 @@ impl EditorProject {
     /// If store is true, we store the new name in the object info hashmap
     pub fn finish_renaming_object(&self, store: bool) {
         if store {
-            if let Some(renaming_object) = self.renaming_object.borrow().as_ref() {
-                let mut object_info = self.object_info.borrow_mut();
-                if let Some(info) = object_info.get_mut(&renaming_object.1) {
-                    info.set_name(renaming_object.2.clone());
+            if let Some((_, object_id, name)) = self.renaming_object.borrow().as_ref() {
+                if let Some(object) = self.pool.object_by_id(*object_id) {
+                    self.set_object_name(object, name.clone());
                 }
             }
         }
         self.renaming_object.replace(None);
     }
 
+    /// Start deleting objects, if cascade is true the children that are no longer used by any
+    /// other object are deleted as well
+    pub fn set_deleting_objects(&self, object_ids: Vec<ObjectId>, cascade: bool) {
+        self.deleting_objects.replace(Some((object_ids, cascade)));
+    }
+
+    /// Get the objects that are being deleted, and whether their unused children are deleted too
+    /// Returns None if no objects are being deleted
+    pub fn get_deleting_objects(&self) -> Option<(Vec<ObjectId>, bool)> {
+        self.deleting_objects.borrow().clone()
+    }
+
+    /// Finish deleting objects
+    /// If delete is true, we remove the objects and all references to them from the pool
+    pub fn finish_deleting_objects(&self, delete: bool) {
+        if let Some((object_ids, cascade)) = self.deleting_objects.take() {
+            if delete {
+                self.label_action(format!("Delete {}", self.objects_label(&object_ids)));
+                self.delete_objects(&self.get_objects_to_delete(&object_ids, cascade));
+            }
+        }
+    }
+
+    /// Get all objects in the current pool that reference the object with the given id
+    pub fn get_referencing_objects(&self, object_id: ObjectId) -> Vec<&Object> {
+        let mut referencing_ids = self
+            .reference_index
+            .get(&object_id)
+            .cloned()
+            .unwrap_or_default();
+        referencing_ids.dedup();
+        referencing_ids
+            .into_iter()
+            .filter_map(|id| self.pool.object_by_id(id))
+            .collect()
+    }
+
+    /// Get the objects that are removed when deleting objects. If cascade is true, this includes
+    /// all objects that are only referenced by the deleted objects.
+    pub fn get_objects_to_delete(&self, object_ids: &[ObjectId], cascade: bool) -> Vec<ObjectId> {
+        let mut to_delete = object_ids.to_vec();
+        if !cascade {
+            return to_delete;
+        }
+
+        // Keep going until no more objects become unused, as children are only unused once all
+        // of their deleted parents are known
+        let mut deleted: HashSet<ObjectId> = object_ids.iter().copied().collect();
+        loop {
+            let candidates: Vec<ObjectId> = to_delete
+                .iter()
+                .filter_map(|id| self.pool.object_by_id(*id))
+                .flat_map(object_references::referenced_ids)
+                .filter(|id| !deleted.contains(id) && self.pool.object_by_id(*id).is_some())
+                .collect();
+
+            let unused: Vec<ObjectId> = candidates
+                .into_iter()
+                .filter(|candidate| {
+                    self.reference_index
+                        .get(candidate)
+                        .is_none_or(|referencing| referencing.iter().all(|id| deleted.contains(id)))
+                })
+                .collect();
+
+            let count = to_delete.len();
+            for id in unused {
+                if deleted.insert(id) {
+                    to_delete.push(id);
+                }
+            }
+            if to_delete.len() == count {
+                return to_delete;
+            }
+        }
+    }
+
+    /// Remove objects and all references to them from the mutating pool
+    fn delete_objects(&self, object_ids: &[ObjectId]) {
+        let mut pool = self.mut_pool.borrow_mut();
+        for object_id in object_ids {
+            pool.remove(*object_id);
+            self.object_changed(*object_id);
+        }
+        for object in pool.objects_mut() {
+            if object_references::remove_references(object, |id| object_ids.contains(&id)) > 0 {
+                self.object_changed(object.id());
+            }
+        }
+        drop(pool);
+
+        let mut selection = self.get_mut_selection();
+        selection.retain(|id| !object_ids.contains(id));
+        self.set_mut_selection(selection);
+    }
+
+    /// Serialize objects and all of their descendants for the clipboard, see
+    /// `object_clipboard::collect_subtree` for which attribute objects are included
+    pub fn copy_objects(
+        &self,
+        object_ids: &[ObjectId],
+        include_attributes: bool,
+    ) -> Option<String> {
+        let copied_ids =
+            object_clipboard::collect_subtree(&self.pool, object_ids, include_attributes);
+        let cloned_objects = |ids: &[ObjectId]| {
+            ids.iter()
+                .filter_map(|id| self.pool.object_by_id(*id))
+                .cloned()
+                .collect()
+        };
+        let objects = cloned_objects(&copied_ids);
+        let referenced_ids = object_clipboard::collect_referenced(&self.pool, &copied_ids);
+        let referenced_objects = cloned_objects(&referenced_ids);
+
+        let object_info = self.object_info.borrow();
+        let names = copied_ids
+            .iter()
+            .chain(&referenced_ids)
+            .filter_map(|id| {
+                let name = object_info.get(id)?.name.clone()?;
+                Some((id.value(), name))
+            })
+            .collect();
+
+        let roots = object_ids
+            .iter()
+            .map(|id| {
+                let offset = self.get_offset_in_parent(*id).unwrap_or_default();
+                ClipboardRoot {
+                    id: id.value(),
+                    x: offset.x,
+                    y: offset.y,
+                }
+            })
+            .collect();
+
+        ClipboardContent::new(objects, names, roots, referenced_objects)
+            .to_text()
+            .ok()
+    }
+
+    /// Paste objects from the clipboard with newly allocated IDs. The pasted objects are added to
+    /// the selected object if it can contain them, and become the new selection.
+    /// References to objects that weren't copied are kept if this project has an identical object
+    /// with that ID, which is the case when pasting into the project they were copied from and the
+    /// object wasn't changed since. Otherwise a copy of the referenced object is pasted as well. References to objects that weren't
+    /// on the clipboard are removed, unless they can't be empty, then nothing is pasted.
+    /// Nothing happens if the text doesn't contain copied objects. Problems are reported with
+    /// `take_clipboard_problems`.
+    pub fn paste_objects(&self, text: &str) {
+        let Some(content) = ClipboardContent::from_text(text) else {
+            return;
+        };
+        let mut objects = content.objects();
+        let mut problems = self.clipboard_problems.borrow_mut();
+
+        // Both objects have the same ID, so comparing them compares their contents
+        let exists = |object: &Object| {
+            self.mut_pool
+                .borrow()
+                .object_by_id(object.id())
+                .is_some_and(|existing| existing == object)
+        };
+        let (existing, missing): (Vec<Object>, Vec<Object>) =
+            content.referenced_objects().into_iter().partition(exists);
+        if !missing.is_empty() {
+            problems.push(format!(
+                "Also pasted {} used by the copied objects, which don't exist in this project or \
+                 differ from the copied ones",
+                count_objects(missing.len())
+            ));
+        }
+        objects.extend(missing);
+
+        let known: HashSet<ObjectId> = objects.iter().chain(&existing).map(Object::id).collect();
+        let required_missing = objects.iter().any(|object| {
+            object_references::referenced_ids(object)
+                .into_iter()
+                .any(|id| !known.contains(&id) && object_references::requires_reference(object, id))
+        });
+        if required_missing {
+            problems.push(
+                "The copied objects use objects that weren't copied and don't exist in this \
+                 project, nothing was pasted"
+                    .to_string(),
+            );
+            return;
+        }
+        let removed: usize = objects
+            .iter_mut()
+            .map(|object| object_references::remove_references(object, |id| !known.contains(&id)))
+            .sum();
+        if removed > 0 {
+            problems.push(format!(
+                "Removed {} from the pasted objects, the referenced objects weren't copied and \
+                 don't exist in this project",
+                match removed {
+                    1 => "1 reference".to_string(),
+                    _ => format!("{} references", removed),
+                }
+            ));
+        }
+
+        let names = content.names();
+        let id_map =
+            match self.insert_copies(objects, |object| names.get(&object.id().value()).cloned()) {
+                Ok(id_map) => id_map,
+                Err(problem) => {
+                    problems.push(problem);
+                    return;
+                }
+            };
+
+        let pasted: Vec<(ObjectId, Point<i16>)> = content
+            .roots()
+            .iter()
+            .filter_map(|root| Some((*id_map.get(&root.object_id()?)?, root.offset())))
+            .collect();
+        if let Some(parent_id) = self.selected_object.0 {
+            let mut orphans = 0;
+            for (id, offset) in &pasted {
+                if !self.add_object_ref(parent_id, *id, *offset) {
+                    orphans += 1;
+                }
+            }
+            if orphans > 0 {
+                problems.push(format!(
+                    "{} of the pasted objects can't be added to {}, they are pasted without a \
+                     parent",
+                    orphans,
+                    self.object_name(parent_id)
+                ));
+            }
+        }
+        self.label_action(format!("Paste {}", count_objects(pasted.len())));
+        self.set_mut_selection(pasted.into_iter().map(|(id, _)| id).collect());
+    }
+
+    /// Duplicate objects, and optionally all of their descendants, with newly allocated IDs.
+    /// Attribute objects, variables and macros are shared with the originals. Custom names are
+    /// copied with a " copy" suffix. The duplicates are added next to the originals in their
+    /// parents and become the new selection. Problems are reported with `take_clipboard_problems`.
+    pub fn duplicate_objects(&self, object_ids: &[ObjectId], include_children: bool) {
+        let duplicated_ids = if include_children {
+            object_clipboard::collect_subtree(&self.pool, object_ids, false)
+        } else {
+            object_ids.to_vec()
+        };
+        let objects: Vec<Object> = duplicated_ids
+            .iter()
+            .filter_map(|id| self.pool.object_by_id(*id))
+            .cloned()
+            .collect();
+
+        let mut existing_names = self.get_all_object_names();
+        let names: HashMap<ObjectId, String> = {
+            let object_info = self.object_info.borrow();
+            objects
+                .iter()
+                .filter_map(|object| {
+                    let name = object_info.get(&object.id())?.name.as_ref()?;
+                    let copy_name = smart_naming::generate_copy_name(name, &existing_names);
+                    *existing_names.entry(copy_name.clone()).or_insert(0) += 1;
+                    Some((object.id(), copy_name))
+                })
+                .collect()
+        };
+        let id_map = match self.insert_copies(objects, |object| names.get(&object.id()).cloned()) {
+            Ok(id_map) => id_map,
+            Err(problem) => {
+                self.clipboard_problems.borrow_mut().push(problem);
+                return;
+            }
+        };
+        self.label_action(format!("Duplicate {}", self.objects_label(object_ids)));
+
+        let duplicates: Vec<ObjectId> = object_ids
+            .iter()
+            .filter_map(|id| {
+                let duplicate_id = *id_map.get(id)?;
+                self.add_duplicate_to_parents(*id, duplicate_id);
+                Some(duplicate_id)
+            })
+            .collect();
+        self.set_mut_selection(duplicates);
+    }
+
+    /// Take the problems of pasting or duplicating objects to report them to the user
+    pub fn take_clipboard_problems(&self) -> Vec<String> {
+        std::mem::take(&mut self.clipboard_problems.borrow_mut())
+    }
+
+    /// Add a duplicate right after the original in every parent that lists it, moved a bit in
+    /// parents that position their children
+    fn add_duplicate_to_parents(&self, original_id: ObjectId, duplicate_id: ObjectId) {
+        let Some(parent_ids) = self.reference_index.get(&original_id) else {
+            return;
+        };
+        // The index lists a parent once for every reference, but it only needs one duplicate
+        let parent_ids: HashSet<ObjectId> = parent_ids.iter().copied().collect();
+        let mut pool = self.mut_pool.borrow_mut();
+        for parent_id in parent_ids {
+            let Some(parent) = pool.object_mut_by_id(parent_id) else {
+                continue;
+            };
+            self.object_changed(parent_id);
+            object_references::visit_references_mut(parent, |reference| match reference {
+                ReferenceMut::ObjectRefs(object_refs) => {
+                    if let Some(index) = object_refs.iter().position(|r| r.id == original_id) {
+                        let offset = object_refs[index].offset;
+                        object_refs.insert(
+                            index + 1,
+                            ObjectRef {
+                                id: duplicate_id,
+                                offset: Point {
+                                    x: offset.x.saturating_add(DUPLICATE_OFFSET),
+                                    y: offset.y.saturating_add(DUPLICATE_OFFSET),
+                                },
+                            },
+                        );
+                    }
+                }
+                ReferenceMut::Ids(ids) => {
+                    if let Some(index) = ids.iter().position(|id| *id == original_id) {
+                        ids.insert(index + 1, duplicate_id);
+                    }
+                }
+                _ => {}
+            });
+        }
+    }
+
+    /// Add copies of objects to the mutating pool with newly allocated IDs. References between
+    /// the copies are remapped, references to other objects are kept. The copies get the name
+    /// returned by `name` for the original object, if any. Macros get an ID that fits the macro
+    /// references of the copies, see `allocate_macro_id`.
+    /// Returns the new ID for every original ID, or an error without adding any copy if there
+    /// are no free IDs for the copied macros.
+    fn insert_copies(
+        &self,
+        objects: Vec<Object>,
+        name: impl Fn(&Object) -> Option<String>,
+    ) -> Result<HashMap<ObjectId, ObjectId>, String> {
+        let byte_referenced: HashSet<ObjectId> = objects
+            .iter()
+            .flat_map(object_references::byte_macro_ids)
+            .collect();
+        let mut id_map = HashMap::new();
+        for object in &objects {
+            let new_id = match object.object_type() {
+                ObjectType::Macro => self
+                    .allocate_macro_id(byte_referenced.contains(&object.id()))
+                    .ok_or_else(|| {
+                        format!(
+                            "There is no free ID for the copy of macro {}, nothing was added",
+                            object.id().value()
+                        )
+                    })?,
+                _ => self.allocate_object_id(),
+            };
+            id_map.insert(object.id(), new_id);
+        }
+
+        for mut object in objects {
+            let name = name(&object);
+            *object.mut_id() = id_map[&object.id()];
+            object_references::map_references(&mut object, |id| id_map.get(&id).copied());
+            if let Some(name) = name {
+                self.set_object_name(&object, name);
+            }
+            self.add_object(object);
+        }
+        Ok(id_map)
+    }
+
+    /// Add a child object to a parent in the mutating pool, if the parent can contain it.
+    /// Returns false if the child wasn't added.
+    fn add_object_ref(&self, parent_id: ObjectId, child_id: ObjectId, offset: Point<i16>) -> bool {
+        let mut pool = self.mut_pool.borrow_mut();
+        let Some(child_type) = pool.object_by_id(child_id).map(Object::object_type) else {
+            return false;
+        };
+        let Some(parent) = pool.object_mut_by_id(parent_id) else {
+            return false;
+        };
+        if !get_allowed_child_refs(parent.object_type(), self.get_vt_version())
+            .contains(&child_type)
+        {
+            return false;
+        }
+        let Some(object_refs) = object_references::object_refs_mut(parent) else {
+            return false;
+        };
+        object_refs.push(ObjectRef {
+            id: child_id,
+            offset,
+        });
+        self.object_changed(parent_id);
+        true
+    }
+
+    /// Get the offset of an object in the first parent that positions it
+    fn get_offset_in_parent(&self, object_id: ObjectId) -> Option<Point<i16>> {
+        self.reference_index
+            .get(&object_id)?
+            .iter()
+            .filter_map(|parent_id| self.pool.object_by_id(*parent_id))
+            .filter_map(object_references::object_refs)
+            .flatten()
+            .find(|object_ref| object_ref.id == object_id)
+            .map(|object_ref| object_ref.offset)
+    }
+
     pub fn sort_objects_by<F>(&mut self, cmp: F)
     where
         F: Fn(&Object, &Object) -> std::cmp::Ordering,
     {
-        self.mut_pool.borrow_mut().objects_mut().sort_by(cmp);
+        self.mut_pool.get_mut().objects_mut().sort_by(cmp);
+        self.order_changed = true;
     }
 
     /// Get all existing object names for validation
@@ -439,12 +1318,15 @@ impl EditorProject {
             self.selected_object.0
         };
         
-        let project = ProjectFile::new(
-            &self.pool,
-            &object_info,
-            self.mask_size,
-            selected,
-        );
+        let settings = ProjectSettings {
+            mask_size: self.mask_size,
+            last_selected: selected.map(|id| id.value()),
+            soft_key_count: self.soft_key_count,
+            soft_key_placement: self.soft_key_placement,
+            vt_version: self.vt_version,
+            grid: self.grid,
+        };
+        let project = ProjectFile::new(&self.pool, &object_info, settings);
         project.to_bytes()
     }
 
@@ -457,6 +1339,10 @@ impl EditorProject {
         
         let mut editor_project = EditorProject::from(pool);
         editor_project.mask_size = settings.mask_size;
+        editor_project.soft_key_count = settings.soft_key_count;
+        editor_project.soft_key_placement = settings.soft_key_placement;
+        editor_project.vt_version = settings.vt_version;
+        editor_project.grid = settings.grid;
         
         // Restore object metadata
         let metadata = project.get_metadata();
@@ -489,3 +1375,127 @@ impl EditorProject {
         Ok(editor_project)
     }
 }
+
+/// Describe a number of objects, e.g. "3 objects"
+fn count_objects(count: usize) -> String {
+    match count {
+        1 => "1 object".to_string(),
+        _ => format!("{} objects", count),
+    }
+}
+
+/// Combine the labels of the actions in a single step, e.g. "Move 3 objects" when moving
+/// multiple objects at once. Returns None if there are no labels.
+fn combine_labels(mut labels: Vec<String>) -> Option<String> {
+    labels.dedup();
+    let verb = |label: &str| label.split(' ').next().unwrap_or_default().to_string();
+    match labels.as_slice() {
+        [] => None,
+        [label] => Some(label.clone()),
+        [first, ..] if labels.iter().all(|label| verb(label) == verb(first)) => {
+            Some(format!("{} {}", verb(first), count_objects(labels.len())))
+        }
+        _ => Some(labels.join(", ")),
+    }
+}
+
+/// Get the IDs of all objects in the order of the pool
+fn object_order(pool: &ObjectPool) -> Vec<ObjectId> {
+    pool.objects().iter().map(Object::id).collect()
+}
+
+/// Map every referenced object to the objects referencing it
+fn build_reference_index(pool: &ObjectPool) -> HashMap<ObjectId, Vec<ObjectId>> {
+    let mut index: HashMap<ObjectId, Vec<ObjectId>> = HashMap::new();
+    for object in pool.objects() {
+        for referenced in object_references::referenced_ids(object) {
+            index.entry(referenced).or_default().push(object.id());
+        }
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use ag_iso_stack::object_pool::object_attributes::{Event, MacroRef};
+
+    use super::*;
+    use crate::default_object;
+
+    fn object_with_id(object_type: ObjectType, id: u16) -> Object {
+        let mut object = default_object(object_type);
+        *object.mut_id() = ObjectId::new(id).unwrap();
+        object
+    }
+
+    fn copy_of(pool: &ObjectPool, object_type: ObjectType, original_id: ObjectId) -> &Object {
+        pool.objects_by_type(object_type)
+            .into_iter()
+            .find(|object| object.id() != original_id)
+            .expect("the object is duplicated")
+    }
+
+    #[test]
+    fn duplicated_button_event_macro_is_remapped() {
+        let button_id = ObjectId::new(1000).unwrap();
+        let macro_id = ObjectId::new(5).unwrap();
+        let mut button = object_with_id(ObjectType::Button, button_id.value());
+        if let Object::Button(o) = &mut button {
+            o.macro_refs.push(MacroRef {
+                macro_id: macro_id.value() as u8,
+                event_id: Event::OnKeyPress,
+            });
+        }
+        let mut pool = ObjectPool::new();
+        pool.add(button);
+        pool.add(object_with_id(ObjectType::Macro, macro_id.value()));
+        let mut project = EditorProject::from(pool);
+
+        project.duplicate_objects(&[button_id, macro_id], false);
+        assert!(project.update_pool());
+        assert!(project.take_clipboard_problems().is_empty());
+
+        let pool = project.get_pool();
+        let macro_copy_id = copy_of(pool, ObjectType::Macro, macro_id).id();
+        assert!(macro_copy_id.value() <= u8::MAX as u16);
+        let Object::Button(button_copy) = copy_of(pool, ObjectType::Button, button_id) else {
+            unreachable!();
+        };
+        assert_eq!(button_copy.macro_refs.len(), 1);
+        assert_eq!(
+            button_copy.macro_refs[0].macro_id as u16,
+            macro_copy_id.value()
+        );
+        let Some(Object::Button(button)) = pool.object_by_id(button_id) else {
+            unreachable!();
+        };
+        assert_eq!(button.macro_refs[0].macro_id as u16, macro_id.value());
+    }
+
+    #[test]
+    fn combined_labels() {
+        let labels = |labels: &[&str]| labels.iter().map(|label| label.to_string()).collect();
+        assert_eq!(combine_labels(Vec::new()), None);
+        assert_eq!(
+            combine_labels(labels(&["Move Button 1"])),
+            Some("Move Button 1".to_string())
+        );
+        // Repeated actions on the same object are a single action
+        assert_eq!(
+            combine_labels(labels(&["Resize Button 1", "Resize Button 1"])),
+            Some("Resize Button 1".to_string())
+        );
+        assert_eq!(
+            combine_labels(labels(&[
+                "Move Button 1",
+                "Move Line 2",
+                "Move Container 3"
+            ])),
+            Some("Move 3 objects".to_string())
+        );
+        assert_eq!(
+            combine_labels(labels(&["Move Button 1", "Resize Line 2"])),
+            Some("Move Button 1, Resize Line 2".to_string())
+        );
+    }
+}
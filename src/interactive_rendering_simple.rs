@@ -2,16 +2,78 @@
 //! SPDX-License-Identifier: GPL-3.0-or-later
 //! Authors: Daan Steenbergen
 
-use ag_iso_stack::object_pool::{object::Object, ObjectId, ObjectPool};
+use ag_iso_stack::object_pool::{
+    object::Object, object::SoftKeyMask, NullableObjectId, ObjectId, ObjectPool, ObjectRef,
+};
 use ag_iso_stack::object_pool::object_attributes::Point;
+use ag_iso_stack::object_pool::vt_version::VtVersion;
 use eframe::egui;
-use crate::RenderableObject;
+use crate::object_references::object_refs;
+use crate::object_rendering::{button_face_rect, resolve_number_value, vt_version};
+use crate::object_sizing::{resizable, set_object_size, Resizable};
+use crate::{GridSettings, RenderableObject, SoftKeyPlacement};
+
+/// Size of the square resize handles around the selected object
+const HANDLE_SIZE: f32 = 8.0;
+
+/// Distance in pixels within which a dragged edge snaps to the grid or another object
+const SNAP_DISTANCE: f32 = 4.0;
+
+/// Clicks closer together than this distance in pixels cycle through the objects at that point
+const CYCLE_CLICK_DISTANCE: f32 = 3.0;
+
+/// Distance in pixels the selected object is moved with the arrow keys while holding shift
+const NUDGE_LARGE_STEP: i16 = 10;
 
 /// Interactive wrapper for rendering masks with clickable objects
 pub struct InteractiveMaskRenderer<'a> {
     pub object: &'a Object,
     pub pool: &'a ObjectPool,
     pub selected_callback: Box<dyn FnMut(ObjectId) + 'a>,
+    /// Called when an object is dragged to a new position, with the parent object, the index of
+    /// the object in the parent's object references and the new offset within the parent
+    pub moved_callback: Box<dyn FnMut(ObjectId, usize, Point<i16>) + 'a>,
+    /// The selected object, which gets resize handles if its size can be changed
+    pub selected: NullableObjectId,
+    /// Called when the selected object is resized, with its new width and height
+    pub resized_callback: Box<dyn FnMut(ObjectId, u16, u16) + 'a>,
+    /// All selected objects, which are outlined and moved together
+    pub selection: Vec<ObjectId>,
+    pub grid: GridSettings,
+}
+
+/// An object shown in the mask
+#[derive(Clone, Copy)]
+struct ObjectHit {
+    object_id: ObjectId,
+    /// Area of the object relative to the widget
+    rect: egui::Rect,
+    /// Area the object is clipped to by its parents, relative to the widget
+    clip: egui::Rect,
+    /// Area of the mask or designator the object is shown in, relative to the widget
+    area: egui::Rect,
+    /// The parent object and the index of the object in the parent's object references,
+    /// or None for the mask itself
+    parent: Option<(ObjectId, usize)>,
+}
+
+/// A resize handle on the border of an object, given as the direction from the object's centre
+#[derive(Clone, Copy, PartialEq)]
+struct ResizeHandle {
+    x: i8,
+    y: i8,
+}
+
+/// State of an object that is being dragged in a preview
+#[derive(Clone, Copy)]
+struct DragState {
+    hit: ObjectHit,
+    /// The handle that is dragged to resize the object, or None if the object is moved
+    handle: Option<ResizeHandle>,
+    /// Offset of the object within its parent when the drag started
+    original_offset: Point<i16>,
+    /// Pointer position when the drag started
+    origin: egui::Pos2,
 }
 
 impl<'a> egui::Widget for InteractiveMaskRenderer<'a> {
@@ -19,7 +81,7 @@ impl<'a> egui::Widget for InteractiveMaskRenderer<'a> {
         // Create an interactive area for the entire mask
         let (width, height) = self.pool.content_size(self.object);
         let desired_size = egui::vec2(width as f32, height as f32);
-        let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::click());
+        let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::click_and_drag());
         
         if ui.is_rect_visible(rect) {
             // Create a child UI for rendering the objects
@@ -27,7 +89,18 @@ impl<'a> egui::Widget for InteractiveMaskRenderer<'a> {
             
             // Render the objects normally
             self.object.render(&mut child_ui, self.pool, Point::default());
-            
+            paint_grid(ui, rect, &self.grid);
+
+            let objects = self.layout(ui.ctx());
+            let mut editor = self.editor();
+            editor.paint_selection(ui, rect, &objects);
+            editor.handle_nudge(ui, &objects);
+
+            // Dragging takes precedence over hovering
+            if editor.handle_drag(ui, rect, &response, &objects) {
+                return response;
+            }
+
             // Handle interaction - check if pointer is interacting with this widget
             if let Some(pointer_pos) = ui.ctx().pointer_hover_pos() {
                 // Check if the pointer is within our allocated rect
@@ -39,13 +112,10 @@ impl<'a> egui::Widget for InteractiveMaskRenderer<'a> {
                     );
                     
                     // Find what object is under the hover position
-                    if let Some((object_id, object_rect)) = self.find_object_at(relative_pos) {
-                        
+                    let stack = objects_at(&objects, relative_pos);
+                    if let Some(hit) = stack.first() {
                         // Draw highlight rectangle around the object
-                        let screen_rect = egui::Rect::from_min_size(
-                            rect.min + object_rect.min.to_vec2(),
-                            object_rect.size()
-                        );
+                        let screen_rect = hit.rect.translate(rect.min.to_vec2());
                         ui.painter().rect_stroke(
                             screen_rect,
                             0.0,
@@ -61,6 +131,8 @@ impl<'a> egui::Widget for InteractiveMaskRenderer<'a> {
                         );
                         
                         if response.clicked() {
+                            let object_id =
+                                self.cycle_selection(ui, &response, &stack, relative_pos);
                             (self.selected_callback)(object_id);
                             ui.ctx().request_repaint(); // Force UI update
                         }
@@ -74,72 +146,927 @@ impl<'a> egui::Widget for InteractiveMaskRenderer<'a> {
 }
 
 impl<'a> InteractiveMaskRenderer<'a> {
-    /// Find which object is at the given position (relative to widget)
-    fn find_object_at(&self, pos: egui::Pos2) -> Option<(ObjectId, egui::Rect)> {
-        self.find_object_recursive(self.object, Point::default(), pos)
+    /// Edit the objects in the mask by dragging them
+    fn editor(&mut self) -> DragEditor<'a, '_> {
+        DragEditor {
+            pool: self.pool,
+            selected: self.selected,
+            selection: &self.selection,
+            grid: self.grid,
+            selected_callback: &mut *self.selected_callback,
+            moved_callback: &mut *self.moved_callback,
+            resized_callback: &mut *self.resized_callback,
+        }
+    }
+
+    /// Get all objects shown in the mask with their areas, in the order they are rendered
+    fn layout(&self, ctx: &egui::Context) -> Vec<ObjectHit> {
+        let (width, height) = self.pool.content_size(self.object);
+        let rect =
+            egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width as f32, height as f32));
+        let mut layout = Layout {
+            pool: self.pool,
+            vt_version: vt_version(ctx),
+            area: rect,
+            objects: Vec::new(),
+        };
+        layout.add_object(self.object, rect, rect, None);
+        layout.objects
     }
-    
-    fn find_object_recursive(
+
+    /// Select the topmost object of the stack under the pointer. Clicking again at the same point
+    /// selects the next object below the selected one, back to the top after the bottom one.
+    fn cycle_selection(
         &self,
-        object: &Object,
-        offset: Point<i16>,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        stack: &[ObjectHit],
         pos: egui::Pos2,
-    ) -> Option<(ObjectId, egui::Rect)> {
-        let (width, height) = self.pool.content_size(object);
-        let rect = egui::Rect::from_min_size(
-            egui::pos2(offset.x as f32, offset.y as f32),
-            egui::vec2(width as f32, height as f32)
-        );
-        
-        // Check children first (they're on top)
-        match object {
-            Object::DataMask(mask) => {
-                for obj_ref in mask.object_refs.iter().rev() {
-                    if let Some(child) = self.pool.object_by_id(obj_ref.id) {
-                        let child_offset = Point {
-                            x: offset.x + obj_ref.offset.x,
-                            y: offset.y + obj_ref.offset.y,
-                        };
-                        if let Some(result) = self.find_object_recursive(child, child_offset, pos) {
-                            return Some(result);
-                        }
-                    }
+    ) -> ObjectId {
+        let last_click_id = response.id.with("last_click");
+        let last_click = ui.data_mut(|data| data.get_temp::<egui::Pos2>(last_click_id));
+        ui.data_mut(|data| data.insert_temp(last_click_id, pos));
+
+        let same_point = last_click.is_some_and(|last| last.distance(pos) <= CYCLE_CLICK_DISTANCE);
+        let selected_index = stack
+            .iter()
+            .position(|hit| self.selected.0 == Some(hit.object_id));
+        match selected_index {
+            Some(index) if same_point => stack[(index + 1) % stack.len()].object_id,
+            _ => stack[0].object_id,
+        }
+    }
+}
+
+/// Selecting, moving and resizing objects by dragging them in a preview, shared by the mask and
+/// the designator previews. The areas of the objects are relative to the preview widget.
+struct DragEditor<'a, 'c> {
+    pool: &'a ObjectPool,
+    selected: NullableObjectId,
+    selection: &'c [ObjectId],
+    grid: GridSettings,
+    selected_callback: &'c mut dyn FnMut(ObjectId),
+    moved_callback: &'c mut dyn FnMut(ObjectId, usize, Point<i16>),
+    resized_callback: &'c mut dyn FnMut(ObjectId, u16, u16),
+}
+
+impl DragEditor<'_, '_> {
+    /// Start, preview and finish dragging an object to a new position within its parent, or
+    /// dragging one of the handles of the selected object to resize it.
+    /// The pool is only changed once the object is released, so a drag is a single undo step.
+    /// Returns true if an object is being dragged.
+    fn handle_drag(
+        &mut self,
+        ui: &mut egui::Ui,
+        rect: egui::Rect,
+        response: &egui::Response,
+        objects: &[ObjectHit],
+    ) -> bool {
+        let drag_id = response.id.with("drag");
+
+        if response.drag_started() {
+            if let Some(drag) = self.start_drag(ui, rect, objects) {
+                ui.data_mut(|data| data.insert_temp(drag_id, drag));
+                // Keep the selection when dragging one of the selected objects, to move them all
+                if !self.selection.contains(&drag.hit.object_id) {
+                    (self.selected_callback)(drag.hit.object_id);
                 }
             }
-            Object::AlarmMask(mask) => {
-                for obj_ref in mask.object_refs.iter().rev() {
-                    if let Some(child) = self.pool.object_by_id(obj_ref.id) {
-                        let child_offset = Point {
-                            x: offset.x + obj_ref.offset.x,
-                            y: offset.y + obj_ref.offset.y,
-                        };
-                        if let Some(result) = self.find_object_recursive(child, child_offset, pos) {
-                            return Some(result);
+        }
+
+        let Some(drag) = ui.data(|data| data.get_temp::<DragState>(drag_id)) else {
+            self.paint_resize_handles(ui, rect, objects);
+            return false;
+        };
+        let Some(object) = self.pool.object_by_id(drag.hit.object_id) else {
+            ui.data_mut(|data| data.remove::<DragState>(drag_id));
+            return false;
+        };
+        let pointer = ui
+            .input(|i| i.pointer.interact_pos())
+            .unwrap_or(drag.origin);
+        let delta = (pointer - drag.origin).round();
+
+        // Determine the new area of the object, resizing only moves the dragged edges
+        let mut new_rect = drag.hit.rect;
+        match drag.handle {
+            None => new_rect = new_rect.translate(delta),
+            Some(handle) => {
+                match handle.x {
+                    -1 => new_rect.min.x = (new_rect.min.x + delta.x).min(new_rect.max.x - 1.0),
+                    1 => new_rect.max.x = (new_rect.max.x + delta.x).max(new_rect.min.x + 1.0),
+                    _ => {}
+                }
+                match handle.y {
+                    -1 => new_rect.min.y = (new_rect.min.y + delta.y).min(new_rect.max.y - 1.0),
+                    1 => new_rect.max.y = (new_rect.max.y + delta.y).max(new_rect.min.y + 1.0),
+                    _ => {}
+                }
+            }
+        }
+
+        // Snap the dragged edges, unless alt is held to place the object freely
+        if !ui.input(|i| i.modifiers.alt) {
+            let (targets_x, targets_y) = self.snap_targets(&drag);
+            // The grid starts at the mask or designator the object is shown in
+            let grid_origin = drag.hit.area.min;
+            match drag.handle {
+                None => {
+                    let dx = self.snap_adjustment(
+                        &[new_rect.min.x, new_rect.center().x, new_rect.max.x],
+                        &targets_x,
+                        grid_origin.x,
+                    );
+                    let dy = self.snap_adjustment(
+                        &[new_rect.min.y, new_rect.center().y, new_rect.max.y],
+                        &targets_y,
+                        grid_origin.y,
+                    );
+                    new_rect = new_rect.translate(egui::vec2(dx, dy));
+                }
+                Some(handle) => {
+                    match handle.x {
+                        -1 => {
+                            new_rect.min.x +=
+                                self.snap_adjustment(&[new_rect.min.x], &targets_x, grid_origin.x);
+                            new_rect.min.x = new_rect.min.x.min(new_rect.max.x - 1.0);
+                        }
+                        1 => {
+                            new_rect.max.x +=
+                                self.snap_adjustment(&[new_rect.max.x], &targets_x, grid_origin.x);
+                            new_rect.max.x = new_rect.max.x.max(new_rect.min.x + 1.0);
+                        }
+                        _ => {}
+                    }
+                    match handle.y {
+                        -1 => {
+                            new_rect.min.y +=
+                                self.snap_adjustment(&[new_rect.min.y], &targets_y, grid_origin.y);
+                            new_rect.min.y = new_rect.min.y.min(new_rect.max.y - 1.0);
+                        }
+                        1 => {
+                            new_rect.max.y +=
+                                self.snap_adjustment(&[new_rect.max.y], &targets_y, grid_origin.y);
+                            new_rect.max.y = new_rect.max.y.max(new_rect.min.y + 1.0);
                         }
+                        _ => {}
                     }
                 }
             }
-            Object::Container(container) => {
-                for obj_ref in container.object_refs.iter().rev() {
-                    if let Some(child) = self.pool.object_by_id(obj_ref.id) {
-                        let child_offset = Point {
-                            x: offset.x + obj_ref.offset.x,
-                            y: offset.y + obj_ref.offset.y,
+        }
+
+        let offset = Point {
+            x: (drag.original_offset.x as f32 + new_rect.min.x - drag.hit.rect.min.x) as i16,
+            y: (drag.original_offset.y as f32 + new_rect.min.y - drag.hit.rect.min.y) as i16,
+        };
+        let size = (
+            new_rect.width().min(u16::MAX as f32) as u16,
+            new_rect.height().min(u16::MAX as f32) as u16,
+        );
+
+        // Preview the moved or resized object on top of the mask or designator it is shown in
+        let mut preview = object.clone();
+        if drag.handle.is_some() {
+            set_object_size(&mut preview, size.0, size.1);
+            let (width, height) = self.pool.content_size(&preview);
+            new_rect.set_width(width as f32);
+            new_rect.set_height(height as f32);
+        }
+        let mut child_ui = ui.new_child(egui::UiBuilder::new().max_rect(rect));
+        child_ui.set_clip_rect(
+            drag.hit
+                .area
+                .translate(rect.min.to_vec2())
+                .intersect(ui.clip_rect()),
+        );
+        let position = Point {
+            x: new_rect.min.x as i16,
+            y: new_rect.min.y as i16,
+        };
+        preview.render(&mut child_ui, self.pool, position);
+        ui.painter().rect_stroke(
+            new_rect.translate(rect.min.to_vec2()),
+            0.0,
+            egui::Stroke::new(
+                2.0,
+                egui::Color32::from_rgba_premultiplied(255, 255, 0, 200),
+            ),
+            egui::epaint::StrokeKind::Middle,
+        );
+
+        // The other selected objects follow a moved selected object
+        let moved = (new_rect.min - drag.hit.rect.min).round();
+        let followers = if drag.handle.is_none() && self.selection.contains(&drag.hit.object_id) {
+            self.movable_selection(objects)
+                .into_iter()
+                .filter(|(hit, _)| hit.object_id != drag.hit.object_id)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        for (hit, _) in &followers {
+            let Some(follower) = self.pool.object_by_id(hit.object_id) else {
+                continue;
+            };
+            let moved_rect = hit.rect.translate(moved);
+            let position = Point {
+                x: moved_rect.min.x as i16,
+                y: moved_rect.min.y as i16,
+            };
+            follower.render(&mut child_ui, self.pool, position);
+            ui.painter().rect_stroke(
+                moved_rect.translate(rect.min.to_vec2()),
+                0.0,
+                egui::Stroke::new(
+                    2.0,
+                    egui::Color32::from_rgba_premultiplied(255, 255, 0, 200),
+                ),
+                egui::epaint::StrokeKind::Middle,
+            );
+        }
+
+        if response.drag_stopped() {
+            ui.data_mut(|data| data.remove::<DragState>(drag_id));
+            if drag.handle.is_some() && new_rect.size() != drag.hit.rect.size() {
+                (self.resized_callback)(drag.hit.object_id, size.0, size.1);
+            }
+            if let Some((parent_id, index)) = drag.hit.parent {
+                if offset != drag.original_offset {
+                    (self.moved_callback)(parent_id, index, offset);
+                }
+            }
+            if moved != egui::Vec2::ZERO {
+                for (hit, offset) in followers {
+                    if let Some((parent_id, index)) = hit.parent {
+                        let offset = Point {
+                            x: offset.x.saturating_add(moved.x as i16),
+                            y: offset.y.saturating_add(moved.y as i16),
                         };
-                        if let Some(result) = self.find_object_recursive(child, child_offset, pos) {
-                            return Some(result);
-                        }
+                        (self.moved_callback)(parent_id, index, offset);
                     }
                 }
             }
+            ui.ctx().request_repaint();
+        }
+        true
+    }
+
+    /// Get the horizontal and vertical positions a dragged object snaps to: the edges and centres
+    /// of its parent and of the other objects in the same parent
+    fn snap_targets(&self, drag: &DragState) -> (Vec<f32>, Vec<f32>) {
+        let mut targets = (Vec::new(), Vec::new());
+        if !self.grid.snap_to_objects {
+            return targets;
+        }
+        let Some((parent_id, index)) = drag.hit.parent else {
+            return targets;
+        };
+        let Some(parent) = self.pool.object_by_id(parent_id) else {
+            return targets;
+        };
+
+        let origin = drag.hit.rect.min
+            - egui::vec2(drag.original_offset.x as f32, drag.original_offset.y as f32);
+        let (width, height) = self.pool.content_size(parent);
+        let mut rects = vec![egui::Rect::from_min_size(
+            origin,
+            egui::vec2(width as f32, height as f32),
+        )];
+        for (sibling_index, obj_ref) in object_refs(parent).into_iter().flatten().enumerate() {
+            if sibling_index == index {
+                continue;
+            }
+            if let Some(sibling) = self.pool.object_by_id(obj_ref.id) {
+                let (width, height) = self.pool.content_size(sibling);
+                rects.push(egui::Rect::from_min_size(
+                    origin + egui::vec2(obj_ref.offset.x as f32, obj_ref.offset.y as f32),
+                    egui::vec2(width as f32, height as f32),
+                ));
+            }
+        }
+        for rect in rects {
+            targets.0.extend([rect.min.x, rect.center().x, rect.max.x]);
+            targets.1.extend([rect.min.y, rect.center().y, rect.max.y]);
+        }
+        targets
+    }
+
+    /// Get the smallest distance that moves one of the edges onto a target or a grid line,
+    /// or zero if none of them are within snapping distance. The grid starts at `grid_origin`.
+    fn snap_adjustment(&self, edges: &[f32], targets: &[f32], grid_origin: f32) -> f32 {
+        let grid_size =
+            (self.grid.snap_to_grid && self.grid.size > 0).then_some(self.grid.size as f32);
+        let mut best: Option<f32> = None;
+        for &edge in edges {
+            let grid_line =
+                grid_size.map(|size| grid_origin + ((edge - grid_origin) / size).round() * size);
+            for target in targets.iter().copied().chain(grid_line) {
+                let distance = target - edge;
+                if distance.abs() <= SNAP_DISTANCE
+                    && best.is_none_or(|best| distance.abs() < best.abs())
+                {
+                    best = Some(distance);
+                }
+            }
+        }
+        best.unwrap_or(0.0)
+    }
+
+    /// Get the selected objects in the preview that can be moved within their parent, together with
+    /// their current offset in the parent
+    fn movable_selection(&self, objects: &[ObjectHit]) -> Vec<(ObjectHit, Point<i16>)> {
+        self.selection
+            .iter()
+            .filter_map(|id| find_object_by_id(objects, *id))
+            .filter_map(|hit| {
+                let (parent_id, index) = hit.parent?;
+                let parent = self.pool.object_by_id(parent_id)?;
+                Some((hit, object_refs(parent)?.get(index)?.offset))
+            })
+            .collect()
+    }
+
+    /// Outline all selected objects when more than one object is selected
+    fn paint_selection(&self, ui: &egui::Ui, rect: egui::Rect, objects: &[ObjectHit]) {
+        if self.selection.len() < 2 {
+            return;
+        }
+        for hit in self
+            .selection
+            .iter()
+            .filter_map(|id| find_object_by_id(objects, *id))
+        {
+            ui.painter().rect_stroke(
+                hit.rect.translate(rect.min.to_vec2()),
+                0.0,
+                egui::Stroke::new(
+                    2.0,
+                    egui::Color32::from_rgba_premultiplied(0, 160, 255, 200),
+                ),
+                egui::epaint::StrokeKind::Middle,
+            );
+        }
+    }
+
+    /// Move the selected objects within their parent with the arrow keys, by one pixel or by
+    /// `NUDGE_LARGE_STEP` pixels while holding shift
+    fn handle_nudge(&mut self, ui: &egui::Ui, objects: &[ObjectHit]) {
+        // The arrow keys are used to edit the focused widget instead
+        if ui.memory(|memory| memory.focused().is_some()) {
+            return;
+        }
+        let movable = self.movable_selection(objects);
+        if movable.is_empty() {
+            return;
+        }
+
+        let mut delta = (0i16, 0i16);
+        ui.input_mut(|i| {
+            for (modifiers, step) in [
+                (egui::Modifiers::NONE, 1),
+                (egui::Modifiers::SHIFT, NUDGE_LARGE_STEP),
+            ] {
+                if i.consume_key(modifiers, egui::Key::ArrowLeft) {
+                    delta.0 -= step;
+                }
+                if i.consume_key(modifiers, egui::Key::ArrowRight) {
+                    delta.0 += step;
+                }
+                if i.consume_key(modifiers, egui::Key::ArrowUp) {
+                    delta.1 -= step;
+                }
+                if i.consume_key(modifiers, egui::Key::ArrowDown) {
+                    delta.1 += step;
+                }
+            }
+        });
+        if delta == (0, 0) {
+            return;
+        }
+
+        for (hit, offset) in movable {
+            if let Some((parent_id, index)) = hit.parent {
+                let offset = Point {
+                    x: offset.x.saturating_add(delta.0),
+                    y: offset.y.saturating_add(delta.1),
+                };
+                (self.moved_callback)(parent_id, index, offset);
+            }
+        }
+    }
+
+    /// Determine what to drag from the position where the pointer was pressed: one of the resize
+    /// handles of the selected object, or otherwise the object under the pointer. The selected
+    /// object is preferred, so an object selected by click-cycling can be dragged as well.
+    fn start_drag(
+        &self,
+        ui: &egui::Ui,
+        rect: egui::Rect,
+        objects: &[ObjectHit],
+    ) -> Option<DragState> {
+        let origin = ui.input(|i| i.pointer.press_origin())?;
+        let relative_origin = origin - rect.min.to_vec2();
+
+        let handle = self
+            .selected_resize_handles(objects)
+            .and_then(|(hit, handles)| {
+                handles
+                    .into_iter()
+                    .find(|handle| handle_rect(hit.rect, *handle).contains(relative_origin))
+                    .map(|handle| (hit, handle))
+            });
+        let (hit, handle) = match handle {
+            Some((hit, handle)) => (hit, Some(handle)),
+            None => {
+                let stack = objects_at(objects, relative_origin);
+                let selected = stack
+                    .iter()
+                    .find(|hit| self.selected.0 == Some(hit.object_id) && hit.parent.is_some());
+                (*selected.or(stack.first())?, None)
+            }
+        };
+
+        // Objects without a parent can only be resized
+        let original_offset = match hit.parent {
+            Some((parent_id, index)) => {
+                let parent = self.pool.object_by_id(parent_id)?;
+                object_refs(parent)?.get(index)?.offset
+            }
+            None if handle.is_some() => Point::default(),
+            None => return None,
+        };
+        Some(DragState {
+            hit,
+            handle,
+            original_offset,
+            origin,
+        })
+    }
+
+    /// Get the selected object and the handles it can be resized with, if any
+    fn selected_resize_handles(
+        &self,
+        objects: &[ObjectHit],
+    ) -> Option<(ObjectHit, Vec<ResizeHandle>)> {
+        let hit = find_object_by_id(objects, self.selected.0?)?;
+        let object = self.pool.object_by_id(hit.object_id)?;
+        let handles = match resizable(object)? {
+            // Without a parent the object can't be moved, so only the right and bottom edges
+            // can be dragged
+            Resizable::Free if hit.parent.is_some() => (-1..=1)
+                .flat_map(|y| (-1..=1).map(move |x| ResizeHandle { x, y }))
+                .filter(|handle| *handle != ResizeHandle { x: 0, y: 0 })
+                .collect(),
+            Resizable::Free => vec![
+                ResizeHandle { x: 1, y: 0 },
+                ResizeHandle { x: 0, y: 1 },
+                ResizeHandle { x: 1, y: 1 },
+            ],
+            Resizable::WidthOnly => vec![ResizeHandle { x: 1, y: 0 }],
+        };
+        Some((hit, handles))
+    }
+
+    fn paint_resize_handles(&self, ui: &egui::Ui, rect: egui::Rect, objects: &[ObjectHit]) {
+        let Some((hit, handles)) = self.selected_resize_handles(objects) else {
+            return;
+        };
+        for handle in handles {
+            let screen_rect = handle_rect(hit.rect, handle).translate(rect.min.to_vec2());
+            ui.painter().rect(
+                screen_rect,
+                0.0,
+                egui::Color32::WHITE,
+                egui::Stroke::new(1.0, egui::Color32::BLACK),
+                egui::epaint::StrokeKind::Inside,
+            );
+        }
+    }
+}
+
+/// The objects shown in a mask with their areas, in the order they are rendered
+struct Layout<'a> {
+    pool: &'a ObjectPool,
+    vt_version: VtVersion,
+    /// Area of the mask or designator the objects that are added next are shown in
+    area: egui::Rect,
+    objects: Vec<ObjectHit>,
+}
+
+impl Layout<'_> {
+    /// Add an object and the objects shown inside of it, placing the children the same way as
+    /// the renderer does. `rect` is the area of the object and `clip` the area it is clipped to.
+    fn add_object(
+        &mut self,
+        object: &Object,
+        rect: egui::Rect,
+        clip: egui::Rect,
+        parent: Option<(ObjectId, usize)>,
+    ) {
+        if let Object::Container(container) = object {
+            if container.hidden {
+                return;
+            }
+        }
+        self.objects.push(ObjectHit {
+            object_id: object.id(),
+            rect,
+            clip,
+            area: self.area,
+            parent,
+        });
+
+        let children = match object {
+            Object::WorkingSet(o) if o.selectable => Some((&o.object_refs, rect)),
+            Object::DataMask(o) => Some((&o.object_refs, rect)),
+            Object::AlarmMask(o) => Some((&o.object_refs, rect)),
+            Object::Container(o) => Some((&o.object_refs, rect)),
+            Object::Key(o) => Some((&o.object_refs, rect)),
+            Object::Button(o) => Some((&o.object_refs, button_face_rect(o, rect, self.vt_version))),
+            _ => None,
+        };
+        if let Some((object_refs, area)) = children {
+            self.add_object_refs(object, object_refs, area, clip);
+        }
+
+        match object {
+            // The pointed object is shown in place of the pointer
+            Object::ObjectPointer(o) => {
+                if let Some(pointed) = o.value.0.and_then(|id| self.pool.object_by_id(id)) {
+                    let rect = object_rect(self.pool, pointed, rect.min);
+                    self.add_object(pointed, rect, clip, None);
+                }
+            }
+            // Only the selected list item is shown, clipped to the list area
+            Object::InputList(o) => {
+                let index = resolve_number_value(self.pool, o.variable_reference, o.value as u32);
+                self.add_list_item(&o.list_items, index, rect, clip);
+            }
+            Object::OutputList(o) => {
+                let index = resolve_number_value(self.pool, o.variable_reference, o.value as u32);
+                self.add_list_item(&o.list_items, index, rect, clip);
+            }
             _ => {}
         }
-        
-        // Then check this object
-        if rect.contains(pos) {
-            Some((object.id(), rect))
+    }
+
+    /// Add the children of `parent`, which are shown relative to and clipped to `area`
+    fn add_object_refs(
+        &mut self,
+        parent: &Object,
+        object_refs: &[ObjectRef],
+        area: egui::Rect,
+        clip: egui::Rect,
+    ) {
+        let clip = clip.intersect(area);
+        for (index, obj_ref) in object_refs.iter().enumerate() {
+            if let Some(child) = self.pool.object_by_id(obj_ref.id) {
+                let min = area.min + egui::vec2(obj_ref.offset.x as f32, obj_ref.offset.y as f32);
+                let rect = object_rect(self.pool, child, min);
+                self.add_object(child, rect, clip, Some((parent.id(), index)));
+            }
+        }
+    }
+
+    fn add_list_item(
+        &mut self,
+        list_items: &[NullableObjectId],
+        index: u32,
+        area: egui::Rect,
+        clip: egui::Rect,
+    ) {
+        let item = list_items
+            .get(index as usize)
+            .and_then(|item| item.0)
+            .and_then(|id| self.pool.object_by_id(id));
+        if let Some(item) = item {
+            let rect = object_rect(self.pool, item, area.min);
+            self.add_object(item, rect, clip.intersect(area), None);
+        }
+    }
+}
+
+/// Paint the grid over the area of a mask or designator on screen
+fn paint_grid(ui: &egui::Ui, rect: egui::Rect, grid: &GridSettings) {
+    // Grid lines closer together than this would hide the mask
+    if !grid.show || grid.size < 2 {
+        return;
+    }
+    let size = grid.size as f32;
+    let stroke = egui::Stroke::new(
+        1.0,
+        egui::Color32::from_rgba_unmultiplied(128, 128, 128, 80),
+    );
+    let painter = ui.painter_at(rect);
+    let mut x = rect.min.x + size;
+    while x < rect.max.x {
+        painter.vline(x, rect.y_range(), stroke);
+        x += size;
+    }
+    let mut y = rect.min.y + size;
+    while y < rect.max.y {
+        painter.hline(rect.x_range(), y, stroke);
+        y += size;
+    }
+}
+
+/// The area of an object with its top left corner at `min`
+fn object_rect(pool: &ObjectPool, object: &Object, min: egui::Pos2) -> egui::Rect {
+    let (width, height) = pool.content_size(object);
+    egui::Rect::from_min_size(min, egui::vec2(width as f32, height as f32))
+}
+
+/// Find where an object is shown in the mask, the first occurrence if it is shown more than once
+fn find_object_by_id(objects: &[ObjectHit], object_id: ObjectId) -> Option<ObjectHit> {
+    objects
+        .iter()
+        .find(|hit| hit.object_id == object_id)
+        .copied()
+}
+
+/// Find all objects at the given position, the topmost first
+fn objects_at(objects: &[ObjectHit], pos: egui::Pos2) -> Vec<ObjectHit> {
+    objects
+        .iter()
+        .rev()
+        .filter(|hit| hit.rect.intersect(hit.clip).contains(pos))
+        .copied()
+        .collect()
+}
+
+/// The area of a resize handle on the border of an object
+fn handle_rect(object_rect: egui::Rect, handle: ResizeHandle) -> egui::Rect {
+    let center = object_rect.center()
+        + egui::vec2(
+            handle.x as f32 * object_rect.width() / 2.0,
+            handle.y as f32 * object_rect.height() / 2.0,
+        );
+    egui::Rect::from_center_size(center, egui::vec2(HANDLE_SIZE, HANDLE_SIZE))
+}
+
+/// Interactive wrapper for rendering a soft key mask as a strip of clickable soft key designators
+pub struct InteractiveSoftKeyMaskRenderer<'a> {
+    pub soft_key_mask: &'a SoftKeyMask,
+    pub pool: &'a ObjectPool,
+    /// Size of a single soft key designator
+    pub key_size: (u16, u16),
+    /// Number of physical soft keys, keys that don't fit are shown on additional pages
+    pub nr_of_keys: u8,
+    pub placement: SoftKeyPlacement,
+    pub selected_callback: Box<dyn FnMut(ObjectId) + 'a>,
+    /// Called when an object in a key is dragged to a new position, see
+    /// `InteractiveMaskRenderer::moved_callback`
+    pub moved_callback: Box<dyn FnMut(ObjectId, usize, Point<i16>) + 'a>,
+    /// The selected object, which gets resize handles if its size can be changed
+    pub selected: NullableObjectId,
+    /// Called when the selected object is resized, with its new width and height
+    pub resized_callback: Box<dyn FnMut(ObjectId, u16, u16) + 'a>,
+    /// All selected objects, which are outlined and moved together
+    pub selection: Vec<ObjectId>,
+    pub grid: GridSettings,
+}
+
+impl<'a> InteractiveSoftKeyMaskRenderer<'a> {
+    /// Edit the objects in the keys by dragging them
+    fn editor(&mut self) -> DragEditor<'a, '_> {
+        DragEditor {
+            pool: self.pool,
+            selected: self.selected,
+            selection: &self.selection,
+            grid: self.grid,
+            selected_callback: &mut *self.selected_callback,
+            moved_callback: &mut *self.moved_callback,
+            resized_callback: &mut *self.resized_callback,
+        }
+    }
+}
+
+impl<'a> egui::Widget for InteractiveSoftKeyMaskRenderer<'a> {
+    fn ui(mut self, ui: &mut egui::Ui) -> egui::Response {
+        let nr_of_keys = self.nr_of_keys.max(1) as usize;
+        let pages = self.soft_key_mask.objects.len().div_ceil(nr_of_keys).max(1);
+        let key_size = egui::vec2(self.key_size.0 as f32, self.key_size.1 as f32);
+        let vertical = self.placement != SoftKeyPlacement::Bottom;
+
+        // Pages are placed next to each other, with the first page closest to the mask
+        let desired_size = if vertical {
+            egui::vec2(key_size.x * pages as f32, key_size.y * nr_of_keys as f32)
         } else {
-            None
+            egui::vec2(key_size.x * nr_of_keys as f32, key_size.y * pages as f32)
+        };
+        let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::click_and_drag());
+
+        if !ui.is_rect_visible(rect) {
+            return response;
+        }
+
+        let background = self
+            .pool
+            .color_by_index(self.soft_key_mask.background_colour);
+        ui.painter().rect_filled(
+            rect,
+            0.0,
+            egui::Color32::from_rgb(background.r, background.g, background.b),
+        );
+
+        let slot_rect = |index: usize| -> egui::Rect {
+            let page = index / nr_of_keys;
+            let page = if self.placement == SoftKeyPlacement::Left {
+                pages - 1 - page
+            } else {
+                page
+            };
+            let slot = index % nr_of_keys;
+            let offset = if vertical {
+                egui::vec2(key_size.x * page as f32, key_size.y * slot as f32)
+            } else {
+                egui::vec2(key_size.x * slot as f32, key_size.y * page as f32)
+            };
+            egui::Rect::from_min_size(rect.min + offset, key_size)
+        };
+
+        // Outline all designators, also the empty ones
+        for index in 0..pages * nr_of_keys {
+            ui.painter().rect_stroke(
+                slot_rect(index),
+                0.0,
+                egui::Stroke::new(1.0, egui::Color32::from_gray(128)),
+                egui::epaint::StrokeKind::Inside,
+            );
         }
+
+        let pointer_pos = ui
+            .ctx()
+            .pointer_hover_pos()
+            .filter(|pos| rect.contains(*pos));
+        // The keys fill their designators, the objects in the keys can be selected as well.
+        // The layout is relative to the widget, like the layout of the mask.
+        let mut layout = Layout {
+            pool: self.pool,
+            vt_version: vt_version(ui.ctx()),
+            area: egui::Rect::NOTHING,
+            objects: Vec::new(),
+        };
+
+        for (index, key_id) in self.soft_key_mask.objects.iter().enumerate() {
+            let key_rect = slot_rect(index);
+            let mut child_ui = ui.new_child(egui::UiBuilder::new().max_rect(key_rect));
+            child_ui.set_clip_rect(key_rect.intersect(ui.clip_rect()));
+            match self.pool.object_by_id(*key_id) {
+                Some(key) => {
+                    key.render(&mut child_ui, self.pool, Point::default());
+                    paint_grid(ui, key_rect, &self.grid);
+                    layout.area = key_rect.translate(-rect.min.to_vec2());
+                    layout.add_object(key, layout.area, layout.area, None);
+                }
+                None => {
+                    child_ui
+                        .colored_label(egui::Color32::RED, format!("Missing key: {:?}", key_id));
+                }
+            }
+        }
+        let objects = layout.objects;
+
+        let mut editor = self.editor();
+        editor.paint_selection(ui, rect, &objects);
+        editor.handle_nudge(ui, &objects);
+        if editor.handle_drag(ui, rect, &response, &objects) {
+            return response;
+        }
+
+        let hovered = pointer_pos.and_then(|pos| {
+            objects_at(&objects, pos - rect.min.to_vec2())
+                .first()
+                .map(|hit| (hit.object_id, hit.rect.translate(rect.min.to_vec2())))
+        });
+
+        // Hovering outside of a key selects the soft key mask itself
+        if let Some(pointer_pos) = pointer_pos {
+            let (object_id, object_rect) = hovered.unwrap_or((self.soft_key_mask.id, rect));
+            paint_hover(ui, object_rect, pointer_pos);
+
+            if response.clicked() {
+                (self.selected_callback)(object_id);
+                ui.ctx().request_repaint(); // Force UI update
+            }
+        }
+
+        response
     }
-}
\ No newline at end of file
+}
+
+/// Interactive wrapper for rendering the working set designator with clickable objects
+pub struct InteractiveWorkingSetRenderer<'a> {
+    pub working_set: &'a Object,
+    pub pool: &'a ObjectPool,
+    /// Size of the designator, which is the size of a soft key designator
+    pub size: (u16, u16),
+    pub selected_callback: Box<dyn FnMut(ObjectId) + 'a>,
+    /// Called when an object in the working set is dragged to a new position, see
+    /// `InteractiveMaskRenderer::moved_callback`
+    pub moved_callback: Box<dyn FnMut(ObjectId, usize, Point<i16>) + 'a>,
+    /// The selected object, which gets resize handles if its size can be changed
+    pub selected: NullableObjectId,
+    /// Called when the selected object is resized, with its new width and height
+    pub resized_callback: Box<dyn FnMut(ObjectId, u16, u16) + 'a>,
+    /// All selected objects, which are outlined and moved together
+    pub selection: Vec<ObjectId>,
+    pub grid: GridSettings,
+}
+
+impl<'a> InteractiveWorkingSetRenderer<'a> {
+    /// Edit the objects in the working set by dragging them
+    fn editor(&mut self) -> DragEditor<'a, '_> {
+        DragEditor {
+            pool: self.pool,
+            selected: self.selected,
+            selection: &self.selection,
+            grid: self.grid,
+            selected_callback: &mut *self.selected_callback,
+            moved_callback: &mut *self.moved_callback,
+            resized_callback: &mut *self.resized_callback,
+        }
+    }
+}
+
+impl<'a> egui::Widget for InteractiveWorkingSetRenderer<'a> {
+    fn ui(mut self, ui: &mut egui::Ui) -> egui::Response {
+        let desired_size = egui::vec2(self.size.0 as f32, self.size.1 as f32);
+        let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::click_and_drag());
+
+        if !ui.is_rect_visible(rect) {
+            return response;
+        }
+
+        let mut child_ui = ui.new_child(egui::UiBuilder::new().max_rect(rect));
+        child_ui.set_clip_rect(rect.intersect(ui.clip_rect()));
+        self.working_set
+            .render(&mut child_ui, self.pool, Point::default());
+        ui.painter().rect_stroke(
+            rect,
+            0.0,
+            egui::Stroke::new(1.0, egui::Color32::from_gray(128)),
+            egui::epaint::StrokeKind::Inside,
+        );
+        paint_grid(ui, rect, &self.grid);
+
+        // The layout is relative to the widget, like the layout of the mask
+        let area = egui::Rect::from_min_size(egui::Pos2::ZERO, rect.size());
+        let mut layout = Layout {
+            pool: self.pool,
+            vt_version: vt_version(ui.ctx()),
+            area,
+            objects: Vec::new(),
+        };
+        layout.add_object(self.working_set, area, area, None);
+        let objects = layout.objects;
+
+        let mut editor = self.editor();
+        editor.paint_selection(ui, rect, &objects);
+        editor.handle_nudge(ui, &objects);
+        if editor.handle_drag(ui, rect, &response, &objects) {
+            return response;
+        }
+
+        let pointer_pos = ui
+            .ctx()
+            .pointer_hover_pos()
+            .filter(|pos| rect.contains(*pos));
+        if let Some(pointer_pos) = pointer_pos {
+            // Hovering outside of the objects selects the working set itself
+            let (object_id, object_rect) = objects_at(&objects, pointer_pos - rect.min.to_vec2())
+                .first()
+                .map(|hit| (hit.object_id, hit.rect.translate(rect.min.to_vec2())))
+                .unwrap_or((self.working_set.id(), rect));
+            paint_hover(ui, object_rect, pointer_pos);
+
+            if response.clicked() {
+                (self.selected_callback)(object_id);
+                ui.ctx().request_repaint(); // Force UI update
+            }
+        }
+
+        response
+    }
+}
+
+/// Highlight the object under the pointer
+fn paint_hover(ui: &egui::Ui, object_rect: egui::Rect, pointer_pos: egui::Pos2) {
+    ui.painter().rect_stroke(
+        object_rect,
+        0.0,
+        egui::Stroke::new(
+            2.0,
+            egui::Color32::from_rgba_premultiplied(255, 255, 0, 200),
+        ),
+        egui::epaint::StrokeKind::Middle,
+    );
+    ui.painter().circle_stroke(
+        pointer_pos,
+        10.0,
+        egui::Stroke::new(
+            2.0,
+            egui::Color32::from_rgba_premultiplied(255, 255, 255, 128),
+        ),
+    );
+}
@@ -3,19 +3,39 @@
 //! Authors: Daan Steenbergen
 
 mod allowed_object_relationships;
+mod bulk_editing;
 mod editor_project;
 mod interactive_rendering_simple;
+mod object_clipboard;
 mod object_configuring;
 mod object_defaults;
 mod object_info;
+mod object_references;
 mod object_rendering;
+mod object_sizing;
 mod possible_events;
 mod project_file;
 mod smart_naming;
+mod undo_history;
+mod validation;
+mod vt_font;
 
 pub use editor_project::EditorProject;
+pub use editor_project::VT_VERSIONS;
 pub use interactive_rendering_simple::InteractiveMaskRenderer;
+pub use interactive_rendering_simple::InteractiveSoftKeyMaskRenderer;
+pub use interactive_rendering_simple::InteractiveWorkingSetRenderer;
 pub use object_configuring::ConfigurableObject;
 pub use object_defaults::default_object;
 pub use object_info::ObjectInfo;
+pub use object_references::requires_reference;
+pub use object_rendering::flashing_frozen;
+pub use object_rendering::set_flashing_frozen;
+pub use object_rendering::set_pool_revision;
+pub use object_rendering::set_vt_version;
 pub use object_rendering::RenderableObject;
+pub use project_file::GridSettings;
+pub use project_file::SoftKeyPlacement;
+pub use validation::validate_pool;
+pub use validation::Severity;
+pub use validation::ValidationIssue;
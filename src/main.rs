@@ -9,10 +9,22 @@ use ag_iso_stack::object_pool::NullableObjectId;
 use ag_iso_stack::object_pool::ObjectId;
 use ag_iso_stack::object_pool::ObjectPool;
 use ag_iso_stack::object_pool::ObjectType;
+use ag_iso_terminal_designer::flashing_frozen;
+use ag_iso_terminal_designer::requires_reference;
+use ag_iso_terminal_designer::set_flashing_frozen;
+use ag_iso_terminal_designer::set_pool_revision;
+use ag_iso_terminal_designer::set_vt_version;
+use ag_iso_terminal_designer::validate_pool;
 use ag_iso_terminal_designer::ConfigurableObject;
 use ag_iso_terminal_designer::EditorProject;
 use ag_iso_terminal_designer::InteractiveMaskRenderer;
+use ag_iso_terminal_designer::InteractiveSoftKeyMaskRenderer;
+use ag_iso_terminal_designer::InteractiveWorkingSetRenderer;
 use ag_iso_terminal_designer::RenderableObject;
+use ag_iso_terminal_designer::Severity;
+use ag_iso_terminal_designer::SoftKeyPlacement;
+use ag_iso_terminal_designer::ValidationIssue;
+use ag_iso_terminal_designer::VT_VERSIONS;
 use eframe::egui;
 use std::future::Future;
 use std::sync::mpsc::Receiver;
@@ -20,6 +32,15 @@ use std::sync::mpsc::Sender;
 
 const OBJECT_HIERARCHY_ID: &str = "object_hierarchy_ui";
 
+/// Number of actions that can be undone when the undo history is first limited
+const DEFAULT_UNDO_DEPTH: usize = 100;
+
+/// Key of the project with unsaved changes in the eframe storage, which is a file on native and
+/// the local storage of the browser on web
+const AUTOSAVE_KEY: &str = "autosave_project";
+/// Time between autosaves of a project with unsaved changes
+const AUTOSAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
 enum FileDialogReason {
     LoadPool,
     LoadProject,
@@ -32,92 +53,129 @@ pub struct DesignerApp {
     file_channel: (Sender<Vec<u8>>, Receiver<Vec<u8>>),
     show_development_popup: bool,
     new_object_dialog: Option<(ObjectType, String)>,
+    validation_issues: Option<Vec<ValidationIssue>>,
+    show_history: bool,
     apply_smart_naming_on_import: bool,
+    /// Maximum number of actions that can be undone, None for unlimited
+    undo_depth: Option<usize>,
+    /// Revision of the project when it was opened or last saved, None if it isn't saved
+    saved_revision: Option<u64>,
+    /// Revision of the project in the autosave
+    autosaved_revision: Option<u64>,
+    /// Receives the revision of the project once it is written to a file
+    saved_channel: (Sender<u64>, Receiver<u64>),
+    /// Project of a previous session with unsaved changes, which is offered to be restored
+    restore_offer: Option<String>,
+    /// Problems of the last paste or duplicate to report to the user, hidden if empty
+    clipboard_problems: Vec<String>,
 }
 
 impl DesignerApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let mut fonts = egui::FontDefinitions::default();
-
-        // TODO: Create font files and load them here
-        //// Install ISO 8859-1 (ISO Latin 1) font
-        // fonts.font_data.insert(
-        //     "iso_latin_1".to_owned(),
-        //     egui::FontData::from_static(include_bytes!("assets/fonts/iso-latin1.ttf")),
-        // );
-        // fonts
-        //     .families
-        //     .get_mut(&egui::FontFamily::Name("ISO Latin 1".into()))
-        //     .unwrap()
-        //     .insert(0, "iso_latin_1".to_owned());
-
-        // // Install ISO 8859-15 (ISO Latin 9) font
-        // fonts.font_data.insert(
-        //     "iso_latin_9".to_owned(),
-        //     egui::FontData::from_static(include_bytes!("assets/fonts/iso-latin9.ttf")),
-        // );
-        // fonts
-        //     .families
-        //     .get_mut(&egui::FontFamily::Name("ISO Latin 9".into()))
-        //     .unwrap()
-        //     .insert(0, "iso_latin_9".to_owned());
-
-        // // Install ISO 8859-2 (ISO Latin 2) font
-        // fonts.font_data.insert(
-        //     "iso_latin_2".to_owned(),
-        //     egui::FontData::from_static(include_bytes!("assets/fonts/iso-latin2.ttf")),
-        // );
-        // fonts
-        //     .families
-        //     .get_mut(&egui::FontFamily::Name("ISO Latin 2".into()))
-        //     .unwrap()
-        //     .insert(0, "iso_latin_2".to_owned());
-
-        // // Install ISO 8859-4 (ISO Latin 4) font
-        // fonts.font_data.insert(
-        //     "iso_latin_4".to_owned(),
-        //     egui::FontData::from_static(include_bytes!("assets/fonts/iso-latin4.ttf")),
-        // );
-        // fonts
-        //     .families
-        //     .get_mut(&egui::FontFamily::Name("ISO Latin 4".into()))
-        //     .unwrap()
-        //     .insert(0, "iso_latin_4".to_owned());
-
-        // // Install ISO 8859-5 (Cyrillic) font
-        // fonts.font_data.insert(
-        //     "iso_cyrillic".to_owned(),
-        //     egui::FontData::from_static(include_bytes!("assets/fonts/iso-cyrillic.ttf")),
-        // );
-        // fonts
-        //     .families
-        //     .get_mut(&egui::FontFamily::Name("ISO Cyrillic".into()))
-        //     .unwrap()
-        //     .insert(0, "iso_cyrillic".to_owned());
-
-        // // Install ISO 8859-7 (Greek) font
-        // fonts.font_data.insert(
-        //     "iso_greek".to_owned(),
-        //     egui::FontData::from_static(include_bytes!("assets/fonts/iso-greek.ttf")),
-        // );
-        // fonts
-        //     .families
-        //     .get_mut(&egui::FontFamily::Name("ISO Greek".into()))
-        //     .unwrap()
-        //     .insert(0, "iso_greek".to_owned());
-
         Self {
             project: None,
             file_dialog_reason: None,
             file_channel: std::sync::mpsc::channel(),
             show_development_popup: true,
             new_object_dialog: None,
+            validation_issues: None,
+            show_history: false,
             apply_smart_naming_on_import: true, // Default to true for better UX
+            undo_depth: None,
+            saved_revision: None,
+            autosaved_revision: None,
+            saved_channel: std::sync::mpsc::channel(),
+            restore_offer: cc
+                .storage
+                .and_then(|storage| storage.get_string(AUTOSAVE_KEY))
+                .filter(|contents| !contents.is_empty()),
+            clipboard_problems: Vec::new(),
         }
     }
 }
 
 impl DesignerApp {
+    /// Start editing a project, replacing the current one
+    fn open_project(&mut self, mut project: EditorProject) {
+        project.set_undo_depth(self.undo_depth);
+        self.saved_revision = Some(project.get_revision());
+        self.autosaved_revision = None;
+        self.project = Some(project);
+        self.validation_issues = None;
+    }
+
+    /// Check if the project has changes since it was opened or last saved
+    fn has_unsaved_changes(&self) -> bool {
+        self.project
+            .as_ref()
+            .is_some_and(|project| self.saved_revision != Some(project.get_revision()))
+    }
+
+    /// Remember which revision of the project is saved, once it is written to a file
+    fn handle_project_saved(&mut self) {
+        while let Ok(revision) = self.saved_channel.1.try_recv() {
+            self.saved_revision = Some(revision);
+        }
+    }
+
+    /// Offer to restore the project of a previous session that ended with unsaved changes
+    fn render_restore_offer(&mut self, ctx: &egui::Context) {
+        if self.restore_offer.is_none() {
+            return;
+        }
+        let mut restore = false;
+        let mut discard = false;
+        egui::Window::new("Restore unsaved project")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("The previous session ended with unsaved changes to a project. Do you want to restore it?");
+                ui.horizontal(|ui| {
+                    restore = ui.button("Restore").clicked();
+                    discard = ui.button("Discard").clicked();
+                });
+            });
+
+        if restore {
+            if let Some(contents) = self.restore_offer.take() {
+                match EditorProject::load_project(contents.into_bytes()) {
+                    Ok(project) => {
+                        self.open_project(project);
+                        // The restored changes are still not saved to a file
+                        self.saved_revision = None;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to restore project: {}", e);
+                    }
+                }
+            }
+        } else if discard {
+            self.restore_offer = None;
+        }
+    }
+
+    /// Report the problems of the last paste
+    fn render_clipboard_problems(&mut self, ctx: &egui::Context) {
+        if self.clipboard_problems.is_empty() {
+            return;
+        }
+        let mut close = false;
+        egui::Window::new("Clipboard")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                for problem in &self.clipboard_problems {
+                    ui.label(problem);
+                }
+                close = ui.button("OK").clicked();
+            });
+        if close {
+            self.clipboard_problems.clear();
+        }
+    }
+
     /// Open a file dialog
     fn open_file_dialog(&mut self, reason: FileDialogReason, ctx: &egui::Context) {
         self.file_dialog_reason = Some(reason);
@@ -146,13 +204,11 @@ impl DesignerApp {
                         let objects: Vec<&Object> = project.get_pool().objects().iter().collect();
                         project.apply_smart_naming_to_objects(&objects);
                     }
-                    self.project = Some(project);
+                    self.open_project(project);
                 }
                 Some(FileDialogReason::LoadProject) => {
                     match EditorProject::load_project(content) {
-                        Ok(project) => {
-                            self.project = Some(project);
-                        }
+                        Ok(project) => self.open_project(project),
                         Err(e) => {
                             log::error!("Failed to load project: {}", e);
                             // TODO: Show error dialog
@@ -161,14 +217,14 @@ impl DesignerApp {
                 }
                 Some(FileDialogReason::OpenImagePictureGraphics(id)) => {
                     if let Some(pool) = &mut self.project {
-                        if let Some(obj) = pool.get_mut_pool().borrow_mut().object_mut_by_id(id) {
+                        pool.edit_object(id, |obj| {
                             match obj {
                                 Object::PictureGraphic(o) => {
                                     // o.load_image(content);
                                 }
                                 _ => (),
                             }
-                        }
+                        });
                     }
                 }
                 _ => (),
@@ -197,6 +253,8 @@ impl DesignerApp {
         if let Some(project) = &self.project {
             match project.save_project() {
                 Ok(contents) => {
+                    let revision = project.get_revision();
+                    let sender = self.saved_channel.0.clone();
                     let task = rfd::AsyncFileDialog::new()
                         .set_file_name("project.aitp")
                         .add_filter("AgIsoTerminal Project", &["aitp"])
@@ -204,7 +262,9 @@ impl DesignerApp {
                     execute(async move {
                         let file = task.await;
                         if let Some(file) = file {
-                            _ = file.write(&contents).await;
+                            if file.write(&contents).await.is_ok() {
+                                _ = sender.send(revision);
+                            }
                         }
                     });
                 }
@@ -238,14 +298,12 @@ fn render_selectable_object(ui: &mut egui::Ui, object: &Object, project: &Editor
             response.request_focus();
         }
     } else {
-        let is_selected = project.get_selected() == object.id().into();
+        let is_selected = project.is_selected(object.id());
         let label_text = format!("{}: {}", u16::from(object.id()), object_info.get_name(object));
         let response = ui.selectable_label(is_selected, label_text);
 
         if response.clicked() {
-            project
-                .get_mut_selected()
-                .replace(NullableObjectId(Some(object.id())));
+            project.select_object(object.id(), ui.input(|i| i.modifiers));
         }
         if response.double_clicked() {
             project.set_renaming_object(this_ui_id, object.id(), object_info.get_name(object));
@@ -256,14 +314,379 @@ fn render_selectable_object(ui: &mut egui::Ui, object: &Object, project: &Editor
                 project.set_renaming_object(this_ui_id, object.id(), object_info.get_name(object));
                 ui.close();
             }
-            if ui.button("Delete").on_hover_text("Delete object").clicked() {
-                project.get_mut_pool().borrow_mut().remove(object.id());
+            let selection = project.get_selection();
+            // Clipboard commands apply to the whole selection if this object is part of it
+            let clipboard_objects = if is_selected {
+                selection.clone()
+            } else {
+                vec![object.id()]
+            };
+            ui.separator();
+            if ui
+                .button("Copy")
+                .on_hover_text("Copy the object and its children, sharing their attributes")
+                .clicked()
+            {
+                copy_objects(ui.ctx(), project, &clipboard_objects, false);
+                ui.close();
+            }
+            if ui
+                .button("Copy with attributes")
+                .on_hover_text(
+                    "Copy the object and its children, including font, line and fill attributes, variables and macros",
+                )
+                .clicked()
+            {
+                copy_objects(ui.ctx(), project, &clipboard_objects, true);
+                ui.close();
+            }
+            if ui
+                .button("Cut")
+                .on_hover_text("Copy the object and its children, and delete them")
+                .clicked()
+            {
+                copy_objects(ui.ctx(), project, &clipboard_objects, false);
+                project.set_deleting_objects(clipboard_objects.clone(), true);
+                ui.close();
+            }
+            if ui
+                .button("Paste")
+                .on_hover_text("Paste copied objects into this object")
+                .clicked()
+            {
+                // The pasted text arrives as a paste event, by then this object is selected
+                project.select_object(object.id(), egui::Modifiers::NONE);
+                ui.ctx().send_viewport_cmd(egui::ViewportCommand::RequestPaste);
+                ui.close();
+            }
+            if ui
+                .button("Duplicate")
+                .on_hover_text("Duplicate the object, sharing its children")
+                .clicked()
+            {
+                project.duplicate_objects(&clipboard_objects, false);
+                ui.close();
+            }
+            if ui
+                .button("Duplicate with children")
+                .on_hover_text("Duplicate the object and all of its children")
+                .clicked()
+            {
+                project.duplicate_objects(&clipboard_objects, true);
+                ui.close();
+            }
+            ui.separator();
+            if is_selected && selection.len() > 1 {
+                if ui
+                    .button(format!("Delete {} selected objects", selection.len()))
+                    .on_hover_text("Delete all selected objects")
+                    .clicked()
+                {
+                    project.set_deleting_objects(selection, false);
+                    ui.close();
+                }
+            } else if ui.button("Delete").on_hover_text("Delete object").clicked() {
+                project.set_deleting_objects(vec![object.id()], false);
                 ui.close();
             }
         });
     }
 }
 
+/// Put the objects and their descendants on the system clipboard
+fn copy_objects(
+    ctx: &egui::Context,
+    project: &EditorProject,
+    object_ids: &[ObjectId],
+    include_attributes: bool,
+) {
+    if let Some(text) = project.copy_objects(object_ids, include_attributes) {
+        ctx.copy_text(text);
+    }
+}
+
+/// Handle the copy, cut, paste and duplicate shortcuts for the selected objects, unless a widget
+/// like a text field has focus and uses them instead. Holding shift while copying includes the
+/// attributes.
+fn handle_clipboard_events(ctx: &egui::Context, project: &EditorProject) {
+    if ctx.memory(|memory| memory.focused().is_some()) {
+        return;
+    }
+    let (events, shift) = ctx.input(|i| (i.events.clone(), i.modifiers.shift));
+    let selection = project.get_selection();
+    for event in events {
+        match event {
+            egui::Event::Copy if !selection.is_empty() => {
+                copy_objects(ctx, project, &selection, shift);
+            }
+            egui::Event::Cut if !selection.is_empty() => {
+                copy_objects(ctx, project, &selection, false);
+                project.set_deleting_objects(selection.clone(), true);
+            }
+            egui::Event::Paste(text) => project.paste_objects(&text),
+            _ => {}
+        }
+    }
+    if !selection.is_empty()
+        && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::D))
+    {
+        project.duplicate_objects(&selection, true);
+    }
+}
+
+fn render_delete_object_dialog(
+    ctx: &egui::Context,
+    project: &EditorProject,
+    object_ids: &[ObjectId],
+    cascade: &mut bool,
+) {
+    let objects: Vec<&Object> = object_ids
+        .iter()
+        .filter_map(|id| project.get_pool().object_by_id(*id))
+        .collect();
+    if objects.is_empty() {
+        project.finish_deleting_objects(false);
+        return;
+    }
+    let object_name = |object: &Object| {
+        format!(
+            "{}: {}",
+            u16::from(object.id()),
+            project.get_object_info(object).get_name(object)
+        )
+    };
+    let title = match objects.as_slice() {
+        [object] => format!("Delete {}", object_name(object)),
+        _ => format!("Delete {} objects", objects.len()),
+    };
+
+    let mut should_delete = false;
+    let mut should_cancel = false;
+
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            // References from objects that are deleted as well don't matter
+            let references: Vec<(&Object, &Object)> = objects
+                .iter()
+                .flat_map(|object| {
+                    project
+                        .get_referencing_objects(object.id())
+                        .into_iter()
+                        .filter(|referencing| !object_ids.contains(&referencing.id()))
+                        .map(move |referencing| (referencing, *object))
+                })
+                .collect();
+            let single = objects.len() == 1;
+            let mut blocked = false;
+            if references.is_empty() {
+                ui.label(if single {
+                    "This object is not referenced by any other object."
+                } else {
+                    "These objects are not referenced by any other object."
+                });
+            } else {
+                ui.label(if single {
+                    "The references to this object will be removed from:"
+                } else {
+                    "The references to these objects will be removed from:"
+                });
+                egui::ScrollArea::vertical()
+                    .id_salt("referencing_objects")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for (referencing, object) in &references {
+                            let required = requires_reference(referencing, object.id());
+                            let text = match (single, required) {
+                                (true, true) => format!(
+                                    " - {} (requires this object)",
+                                    object_name(referencing)
+                                ),
+                                (true, false) => format!(" - {}", object_name(referencing)),
+                                (false, true) => format!(
+                                    " - {} (requires {})",
+                                    object_name(referencing),
+                                    object_name(object)
+                                ),
+                                (false, false) => format!(
+                                    " - {} (references {})",
+                                    object_name(referencing),
+                                    object_name(object)
+                                ),
+                            };
+                            if required {
+                                blocked = true;
+                                ui.colored_label(egui::Color32::RED, text);
+                            } else {
+                                ui.label(text);
+                            }
+                        }
+                    });
+            }
+
+            ui.add_space(10.0);
+            if ui
+                .checkbox(cascade, "Also delete children that are no longer used")
+                .changed()
+            {
+                project.set_deleting_objects(object_ids.to_vec(), *cascade);
+            }
+            if *cascade {
+                let to_delete = project.get_objects_to_delete(object_ids, true);
+                ui.label(format!("{} objects will be deleted", to_delete.len()));
+            }
+
+            if blocked {
+                ui.add_space(10.0);
+                ui.colored_label(
+                    egui::Color32::RED,
+                    if single {
+                        "Change the references marked in red before deleting this object."
+                    } else {
+                        "Change the references marked in red before deleting these objects."
+                    },
+                );
+            }
+
+            // Check for Escape key
+            if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                should_cancel = true;
+            }
+
+            ui.add_space(20.0);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!blocked, egui::Button::new("Delete"))
+                    .clicked()
+                {
+                    should_delete = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    should_cancel = true;
+                }
+            });
+        });
+
+    if should_delete {
+        project.finish_deleting_objects(true);
+    } else if should_cancel {
+        project.finish_deleting_objects(false);
+    }
+}
+
+/// Show the actions in the undo history, clicking an action undoes or redoes everything after it
+fn render_history(ui: &mut egui::Ui, project: &mut EditorProject) {
+    let (undo_labels, redo_labels) = project.get_history_labels();
+    let position = undo_labels.len();
+    let mut jump_to = None;
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        if ui
+            .selectable_label(position == 0, "Start of history")
+            .clicked()
+        {
+            jump_to = Some(0);
+        }
+        for (index, label) in undo_labels.iter().enumerate() {
+            if ui.selectable_label(index + 1 == position, label).clicked() {
+                jump_to = Some(index + 1);
+            }
+        }
+        // Undone actions are shown greyed out until they are redone or replaced by a new action
+        for (index, label) in redo_labels.iter().enumerate() {
+            if ui
+                .selectable_label(false, egui::RichText::new(label).weak())
+                .clicked()
+            {
+                jump_to = Some(position + index + 1);
+            }
+        }
+    });
+
+    if let Some(position) = jump_to {
+        project.jump_to_history(position);
+    }
+}
+
+/// Show the issues found in the object pool, with links to select the offending objects
+fn render_validation_issues(
+    ui: &mut egui::Ui,
+    project: &EditorProject,
+    issues: &[ValidationIssue],
+) {
+    if issues.is_empty() {
+        ui.label("No issues found");
+        return;
+    }
+    let error_count = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    ui.label(format!(
+        "{} errors, {} warnings",
+        error_count,
+        issues.len() - error_count
+    ));
+    ui.separator();
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("validation_issues_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for issue in issues {
+                    match issue.severity {
+                        Severity::Error => ui.colored_label(egui::Color32::RED, "Error"),
+                        Severity::Warning => ui.colored_label(egui::Color32::YELLOW, "Warning"),
+                    };
+                    match issue
+                        .object_id
+                        .and_then(|id| project.get_pool().object_by_id(id))
+                    {
+                        Some(object) => {
+                            let name = project.get_object_info(object).get_name(object);
+                            if ui
+                                .link(format!("{}: {}", u16::from(object.id()), name))
+                                .clicked()
+                            {
+                                *project.get_mut_selected().borrow_mut() = object.id().into();
+                            }
+                        }
+                        None => {
+                            ui.label("Object pool");
+                        }
+                    }
+                    ui.label(&issue.message);
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+/// Show the objects that reference the given object, with links to select them
+fn render_used_by(ui: &mut egui::Ui, project: &EditorProject, object_id: ObjectId) {
+    let referencing_objects = project.get_referencing_objects(object_id);
+    egui::CollapsingHeader::new(format!("Used by ({})", referencing_objects.len()))
+        .id_salt("used_by")
+        .default_open(true)
+        .show(ui, |ui| {
+            if referencing_objects.is_empty() {
+                ui.label("Not used by any other object");
+            }
+            for object in referencing_objects {
+                let name = project.get_object_info(object).get_name(object);
+                if ui
+                    .link(format!("{}: {}", u16::from(object.id()), name))
+                    .on_hover_text(format!("{:?}", object.object_type()))
+                    .clicked()
+                {
+                    *project.get_mut_selected().borrow_mut() = object.id().into();
+                }
+            }
+        });
+}
+
 fn render_object_hierarchy(
     ui: &mut egui::Ui,
     parent_id: egui::Id,
@@ -335,6 +758,38 @@ fn update_object_hierarchy_headers(
 }
 
 impl eframe::App for DesignerApp {
+    /// Autosave the project if it has unsaved changes, so it can be restored after a crash
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // Keep the autosave of the previous session until the user decided to restore it or not
+        if self.restore_offer.is_some() {
+            return;
+        }
+        let Some(project) = self.project.as_ref().filter(|_| self.has_unsaved_changes()) else {
+            storage.set_string(AUTOSAVE_KEY, String::new());
+            self.autosaved_revision = None;
+            return;
+        };
+        if self.autosaved_revision == Some(project.get_revision()) {
+            return;
+        }
+        match project.save_project() {
+            Ok(contents) => {
+                storage.set_string(
+                    AUTOSAVE_KEY,
+                    String::from_utf8_lossy(&contents).into_owned(),
+                );
+                self.autosaved_revision = Some(project.get_revision());
+            }
+            Err(e) => {
+                log::error!("Failed to autosave project: {}", e);
+            }
+        }
+    }
+
+    fn auto_save_interval(&self) -> std::time::Duration {
+        AUTOSAVE_INTERVAL
+    }
+
     fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
         ctx.style_mut(|style| {
             style.interaction.selectable_labels = false;
@@ -342,6 +797,7 @@ impl eframe::App for DesignerApp {
 
         // Handle file dialog
         self.handle_file_loaded();
+        self.handle_project_saved();
 
         if self.show_development_popup {
             egui::Window::new("🚧 Under Active Development")
@@ -369,6 +825,9 @@ impl eframe::App for DesignerApp {
             return;
         }
 
+        self.render_restore_offer(ctx);
+        self.render_clipboard_problems(ctx);
+
         // Show new object name dialog
         if let Some((object_type, mut name)) = self.new_object_dialog.clone() {
             let mut should_create = false;
@@ -415,23 +874,26 @@ impl eframe::App for DesignerApp {
                 if let Some(pool) = &mut self.project {
                     let mut new_obj = ag_iso_terminal_designer::default_object(object_type);
                     
-                    // Allocate a new ID efficiently
-                    let id = pool.allocate_object_id();
-                    new_obj.mut_id().set_value(id.value()).ok();
-                    
-                    // Add object to pool
-                    pool.get_mut_pool().borrow_mut().add(new_obj.clone());
-                    
-                    // Set the custom name
-                    let mut object_info = pool.object_info.borrow_mut();
-                    let info = object_info
-                        .entry(new_obj.id())
-                        .or_insert_with(|| ag_iso_terminal_designer::ObjectInfo::new(&new_obj));
-                    info.set_name(name);
-                    drop(object_info);
-                    
-                    // Select the new object
-                    pool.get_mut_selected().replace(NullableObjectId::new(id.value()));
+                    // Allocate a new ID efficiently, macros need one that objects can reference
+                    let id = match object_type {
+                        ObjectType::Macro => pool.allocate_macro_id(false),
+                        _ => Some(pool.allocate_object_id()),
+                    };
+                    if let Some(id) = id {
+                        new_obj.mut_id().set_value(id.value()).ok();
+
+                        // Set the custom name
+                        pool.set_object_name(&new_obj, name);
+
+                        // Add object to pool
+                        pool.add_object(new_obj);
+
+                        // Select the new object
+                        pool.get_mut_selected()
+                            .replace(NullableObjectId::new(id.value()));
+                    } else {
+                        log::error!("There is no free ID for a new macro");
+                    }
                 }
                 self.new_object_dialog = None;
             } else if should_cancel {
@@ -442,6 +904,37 @@ impl eframe::App for DesignerApp {
             }
         }
 
+        // Show delete object dialog
+        if let Some(pool) = &self.project {
+            if let Some((object_ids, mut cascade)) = pool.get_deleting_objects() {
+                render_delete_object_dialog(ctx, pool, &object_ids, &mut cascade);
+            }
+        }
+
+        // Show validation report
+        if let (Some(pool), Some(issues)) = (&self.project, &self.validation_issues) {
+            let mut open = true;
+            egui::Window::new("Validation report")
+                .open(&mut open)
+                .default_width(500.0)
+                .show(ctx, |ui| {
+                    render_validation_issues(ui, pool, issues);
+                });
+            if !open {
+                self.validation_issues = None;
+            }
+        }
+
+        // Show undo history
+        if let Some(pool) = &mut self.project {
+            egui::Window::new("History")
+                .open(&mut self.show_history)
+                .default_width(250.0)
+                .show(ctx, |ui| {
+                    render_history(ui, pool);
+                });
+        }
+
         egui::TopBottomPanel::top("topbar").show(ctx, |ui| {
             ui.horizontal_wrapped(|ui| {
                 egui::widgets::global_theme_preference_buttons(ui);
@@ -476,6 +969,8 @@ impl eframe::App for DesignerApp {
                     {
                         pool.redo();
                     }
+                    ui.toggle_value(&mut self.show_history, "History")
+                        .on_hover_text("Show all actions that can be undone or redone");
                     ui.separator();
                 }
 
@@ -500,6 +995,25 @@ impl eframe::App for DesignerApp {
                     
                     ui.checkbox(&mut self.apply_smart_naming_on_import, "Apply smart naming on import")
                         .on_hover_text("Automatically apply smart naming to objects when importing IOP files");
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        let mut limited = self.undo_depth.is_some();
+                        let mut depth = self.undo_depth.unwrap_or(DEFAULT_UNDO_DEPTH);
+                        ui.checkbox(&mut limited, "Limit undo history to")
+                            .on_hover_text("Keep the history unlimited to undo every change since opening");
+                        ui.add_enabled(
+                            limited,
+                            egui::DragValue::new(&mut depth).range(1..=10000).suffix(" actions"),
+                        );
+                        let undo_depth = limited.then_some(depth);
+                        if undo_depth != self.undo_depth {
+                            self.undo_depth = undo_depth;
+                            if let Some(pool) = &mut self.project {
+                                pool.set_undo_depth(undo_depth);
+                            }
+                        }
+                    });
                     if self.project.is_some() && ui.button("Export IOP (.iop)").clicked() {
                         self.save_pool();
                         ui.close();
@@ -523,18 +1037,95 @@ impl eframe::App for DesignerApp {
                     });
                 }
 
+                if let Some(pool) = &self.project {
+                    if ui
+                        .button("Validate")
+                        .on_hover_text("Check the object pool for issues that a VT would reject")
+                        .clicked()
+                    {
+                        self.validation_issues =
+                            Some(validate_pool(pool.get_pool(), pool.get_vt_version()));
+                    }
+                }
+
                 if let Some(pool) = &mut self.project {
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.add(
                             egui::Slider::new(&mut pool.mask_size, 100..=2000)
                                 .text("Virtual Mask size"),
                         );
+                        ui.menu_button("Soft keys", |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut pool.soft_key_count, 6..=64)
+                                    .text("Physical soft keys"),
+                            );
+                            ui.label("Placement:");
+                            ui.radio_value(
+                                &mut pool.soft_key_placement,
+                                SoftKeyPlacement::Left,
+                                "Left",
+                            );
+                            ui.radio_value(
+                                &mut pool.soft_key_placement,
+                                SoftKeyPlacement::Right,
+                                "Right",
+                            );
+                            ui.radio_value(
+                                &mut pool.soft_key_placement,
+                                SoftKeyPlacement::Bottom,
+                                "Bottom",
+                            );
+                        });
+                        ui.menu_button("Grid", |ui| {
+                            ui.checkbox(&mut pool.grid.show, "Show grid");
+                            ui.add(
+                                egui::Slider::new(&mut pool.grid.size, 2..=100)
+                                    .text("Grid size"),
+                            );
+                            ui.checkbox(&mut pool.grid.snap_to_grid, "Snap to grid");
+                            ui.checkbox(&mut pool.grid.snap_to_objects, "Snap to objects")
+                                .on_hover_text(
+                                    "Snap to the edges and centres of the parent and sibling objects",
+                                );
+                            ui.label("Hold alt while dragging to place objects freely");
+                        });
+                        egui::ComboBox::from_id_salt("vt_version")
+                            .selected_text(format!("VT version {}", pool.vt_version))
+                            .show_ui(ui, |ui| {
+                                for version in 0..VT_VERSIONS.len() as u8 {
+                                    ui.selectable_value(
+                                        &mut pool.vt_version,
+                                        version,
+                                        format!("Version {}", version),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text(
+                                "The VT version the object pool is designed for, this determines the available attributes and objects",
+                            );
+                        let mut frozen = flashing_frozen(ctx);
+                        if ui
+                            .checkbox(&mut frozen, "Freeze flashing")
+                            .on_hover_text("Stop flashing text in the preview from animating")
+                            .changed()
+                        {
+                            set_flashing_frozen(ctx, frozen);
+                        }
                     });
                 }
             });
         });
 
         if let Some(pool) = &mut self.project {
+            set_vt_version(ctx, pool.get_vt_version());
+            set_pool_revision(ctx, pool.get_revision());
+            handle_clipboard_events(ctx, pool);
+            let clipboard_problems = pool.take_clipboard_problems();
+            if !clipboard_problems.is_empty() {
+                self.clipboard_problems = clipboard_problems;
+            }
+
             // Set forward and backward navigation shortcuts to mouse buttons
             if ctx.input(|i| i.pointer.button_released(egui::PointerButton::Extra1)) {
                 pool.set_previous_selected();
@@ -658,19 +1249,112 @@ impl eframe::App for DesignerApp {
                     match pool.get_pool().working_set_object() {
                         Some(mask) => match pool.get_pool().object_by_id(mask.active_mask) {
                             Some(obj) => {
-                                let selected_ref = pool.get_mut_selected();
-                                
-                                egui::ScrollArea::both().show(ui, |ui| {
+                                // The soft key mask that belongs to the displayed mask
+                                let soft_key_mask_id = match obj {
+                                    Object::DataMask(o) => o.soft_key_mask.into(),
+                                    Object::AlarmMask(o) => o.soft_key_mask.into(),
+                                    _ => None,
+                                };
+                                let soft_key_mask = soft_key_mask_id.and_then(|id| {
+                                    match pool.get_pool().object_by_id(id) {
+                                        Some(Object::SoftKeyMask(o)) => Some(o),
+                                        _ => None,
+                                    }
+                                });
+
+                                let render_mask = |ui: &mut egui::Ui| {
                                     ui.add_sized(
                                         [pool.mask_size as f32, pool.mask_size as f32],
                                         InteractiveMaskRenderer {
                                             object: obj,
                                             pool: pool.get_pool(),
-                                            selected_callback: Box::new(move |object_id| {
-                                                *selected_ref.borrow_mut() = NullableObjectId(Some(object_id));
+                                            selected_callback: Box::new(|object_id| {
+                                                let modifiers = ctx.input(|i| i.modifiers);
+                                                pool.select_object(object_id, modifiers);
+                                            }),
+                                            moved_callback: Box::new(|parent_id, index, offset| {
+                                                pool.move_object_ref(parent_id, index, offset);
                                             }),
+                                            selected: pool.get_selected(),
+                                            resized_callback: Box::new(
+                                                |object_id, width, height| {
+                                                    pool.resize_object(object_id, width, height);
+                                                },
+                                            ),
+                                            grid: pool.grid,
+                                            selection: pool.get_selection(),
                                         },
                                     );
+                                };
+                                let render_soft_keys = |ui: &mut egui::Ui| {
+                                    if let Some(soft_key_mask) = soft_key_mask {
+                                        ui.add(InteractiveSoftKeyMaskRenderer {
+                                            soft_key_mask,
+                                            pool: pool.get_pool(),
+                                            key_size: pool.get_soft_key_size(),
+                                            nr_of_keys: pool.soft_key_count,
+                                            placement: pool.soft_key_placement,
+                                            selected_callback: Box::new(|object_id| {
+                                                let modifiers = ctx.input(|i| i.modifiers);
+                                                pool.select_object(object_id, modifiers);
+                                            }),
+                                            moved_callback: Box::new(|parent_id, index, offset| {
+                                                pool.move_object_ref(parent_id, index, offset);
+                                            }),
+                                            selected: pool.get_selected(),
+                                            resized_callback: Box::new(
+                                                |object_id, width, height| {
+                                                    pool.resize_object(object_id, width, height);
+                                                },
+                                            ),
+                                            grid: pool.grid,
+                                            selection: pool.get_selection(),
+                                        });
+                                    }
+                                };
+                                let render_working_set = |ui: &mut egui::Ui| {
+                                    let working_set = pool.get_pool().object_by_id(mask.id);
+                                    if let Some(working_set) = working_set {
+                                        ui.add(InteractiveWorkingSetRenderer {
+                                            working_set,
+                                            pool: pool.get_pool(),
+                                            size: pool.get_soft_key_size(),
+                                            selected_callback: Box::new(|object_id| {
+                                                let modifiers = ctx.input(|i| i.modifiers);
+                                                pool.select_object(object_id, modifiers);
+                                            }),
+                                            moved_callback: Box::new(|parent_id, index, offset| {
+                                                pool.move_object_ref(parent_id, index, offset);
+                                            }),
+                                            selected: pool.get_selected(),
+                                            resized_callback: Box::new(
+                                                |object_id, width, height| {
+                                                    pool.resize_object(object_id, width, height);
+                                                },
+                                            ),
+                                            grid: pool.grid,
+                                            selection: pool.get_selection(),
+                                        });
+                                    }
+                                };
+
+                                egui::ScrollArea::both().show(ui, |ui| {
+                                    render_working_set(ui);
+                                    let layout = match pool.soft_key_placement {
+                                        SoftKeyPlacement::Bottom => {
+                                            egui::Layout::top_down(egui::Align::Min)
+                                        }
+                                        _ => egui::Layout::left_to_right(egui::Align::Min),
+                                    };
+                                    ui.with_layout(layout, |ui| {
+                                        if pool.soft_key_placement == SoftKeyPlacement::Left {
+                                            render_soft_keys(ui);
+                                            render_mask(ui);
+                                        } else {
+                                            render_mask(ui);
+                                            render_soft_keys(ui);
+                                        }
+                                    });
                                 });
                             }
                             None => {
@@ -692,43 +1376,99 @@ impl eframe::App for DesignerApp {
 
             // Parameters panel
             egui::SidePanel::right("right_panel").show(ctx, |ui: &mut egui::Ui| {
+                let mut bulk_edit = None;
                 if let Some(id) = pool.get_selected().into() {
-                    if let Some(obj) = pool.get_mut_pool().borrow_mut().object_mut_by_id(id) {
+                    let found = pool.edit_object(id, |obj| {
+                        let selection = pool.get_selection();
+                        let same_type = selection.iter().all(|id| {
+                            pool.get_pool()
+                                .object_by_id(*id)
+                                .is_some_and(|o| o.object_type() == obj.object_type())
+                        });
+                        if selection.len() > 1 {
+                            if same_type {
+                                ui.label(format!(
+                                    "{} objects selected, changes to their common attributes are applied to all of them",
+                                    selection.len()
+                                ));
+                            } else {
+                                ui.colored_label(
+                                    egui::Color32::YELLOW,
+                                    format!(
+                                        "{} objects of different types selected, only this object is edited",
+                                        selection.len()
+                                    ),
+                                );
+                            }
+                            ui.separator();
+                        }
+
                         // Display editable object name as header
                         ui.horizontal(|ui| {
                             ui.label("Name:");
                             
-                            let object_info = pool.get_object_info(obj);
-                            let mut name = object_info.get_name(obj);
+                            // The name is stored once editing finishes, so renaming is a single
+                            // step in the history instead of one for every typed character
+                            let edit_storage = ui.id().with("object_name_edit");
+                            let current_name = pool.get_object_info(obj).get_name(obj);
+                            let mut name = ui
+                                .data(|data| data.get_temp::<(ObjectId, String)>(edit_storage))
+                                .filter(|(edited_id, _)| *edited_id == obj.id())
+                                .map_or_else(|| current_name.clone(), |(_, name)| name);
                             let response = ui.text_edit_singleline(&mut name);
-                            
-                            if response.changed() {
-                                let mut object_info_map = pool.object_info.borrow_mut();
-                                if let Some(info) = object_info_map.get_mut(&obj.id()) {
-                                    info.set_name(name);
+
+                            if response.lost_focus() {
+                                ui.data_mut(|data| {
+                                    data.remove_temp::<(ObjectId, String)>(edit_storage)
+                                });
+                                let cancelled = ui.input(|i| i.key_pressed(egui::Key::Escape));
+                                if !cancelled && name != current_name {
+                                    pool.set_object_name(obj, name);
                                 }
+                            } else if response.has_focus() {
+                                ui.data_mut(|data| {
+                                    data.insert_temp(edit_storage, (obj.id(), name))
+                                });
                             }
                         });
                         ui.separator();
                         
+                        let before = obj.clone();
                         obj.render_parameters(ui, pool);
+                        // Objects of different types are only edited one at a time
+                        if selection.len() > 1 && same_type && *obj != before {
+                            bulk_edit = Some((before, obj.clone()));
+                        }
+                        ui.separator();
+                        render_used_by(ui, pool, id);
                         let (width, height) = pool.get_pool().content_size(obj);
                         ui.separator();
                         let desired_size = egui::Vec2::new(width as f32, height as f32);
                         ui.allocate_ui(desired_size, |ui| {
                             obj.render(ui, pool.get_pool(), Point::default());
                         });
-                    } else {
+                    });
+                    if found.is_none() {
                         ui.colored_label(
                             egui::Color32::RED,
                             format!("Selected object not found: {}", u16::from(id)),
                         );
                     }
                 }
+                // Apply the changes to the other selected objects in the same frame, so all
+                // objects are changed in a single undo step
+                if let Some((before, after)) = bulk_edit {
+                    pool.apply_to_selection(&before, &after);
+                }
                 ui.allocate_space(ui.available_size());
             });
 
             if pool.update_pool() {
+                // Keep the validation report up to date while it is shown
+                if self.validation_issues.is_some() {
+                    self.validation_issues =
+                        Some(validate_pool(pool.get_pool(), pool.get_vt_version()));
+                }
                 ctx.request_repaint();
             }
             if pool.update_selected() {
@@ -0,0 +1,181 @@
+//! Copyright 2024 - The Open-Agriculture Developers
+//! SPDX-License-Identifier: GPL-3.0-or-later
+//! Authors: Daan Steenbergen
+
+use std::collections::{HashMap, HashSet};
+
+use ag_iso_stack::object_pool::{
+    object::Object, object_attributes::Point, ObjectId, ObjectPool, ObjectType,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::object_references::referenced_ids;
+
+/// Identifies text on the clipboard as copied objects
+const CLIPBOARD_FORMAT: &str = "AgIsoTerminalDesigner objects";
+
+/// Clipboard format version
+const CLIPBOARD_VERSION: u32 = 1;
+
+/// Objects copied to the system clipboard, serialized as JSON text so they can be pasted in
+/// another editor instance
+#[derive(Serialize, Deserialize)]
+pub struct ClipboardContent {
+    format: String,
+    version: u32,
+
+    /// The copied objects as IOP bytes
+    object_pool_data: Vec<u8>,
+
+    /// Custom names of the copied objects
+    names: HashMap<u16, String>,
+
+    /// The copied objects that were selected, the others are their descendants
+    roots: Vec<ClipboardRoot>,
+
+    /// The objects referenced by the copied objects that weren't copied themselves, as IOP
+    /// bytes. They are pasted as well if the target project doesn't have them.
+    referenced_object_data: Vec<u8>,
+}
+
+/// A selected object on the clipboard, with its offset in the parent it was copied from
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ClipboardRoot {
+    pub id: u16,
+    pub x: i16,
+    pub y: i16,
+}
+
+impl ClipboardRoot {
+    pub fn object_id(&self) -> Option<ObjectId> {
+        ObjectId::new(self.id).ok()
+    }
+
+    pub fn offset(&self) -> Point<i16> {
+        Point {
+            x: self.x,
+            y: self.y,
+        }
+    }
+}
+
+impl ClipboardContent {
+    pub fn new(
+        objects: Vec<Object>,
+        names: HashMap<u16, String>,
+        roots: Vec<ClipboardRoot>,
+        referenced_objects: Vec<Object>,
+    ) -> Self {
+        ClipboardContent {
+            format: CLIPBOARD_FORMAT.to_string(),
+            version: CLIPBOARD_VERSION,
+            object_pool_data: as_iop(objects),
+            names,
+            roots,
+            referenced_object_data: as_iop(referenced_objects),
+        }
+    }
+
+    /// Serialize the clipboard content to text
+    pub fn to_text(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserialize clipboard content from text
+    /// Returns None if the text doesn't contain copied objects
+    pub fn from_text(text: &str) -> Option<Self> {
+        let content: ClipboardContent = serde_json::from_str(text).ok()?;
+        (content.format == CLIPBOARD_FORMAT && content.version <= CLIPBOARD_VERSION)
+            .then_some(content)
+    }
+
+    pub fn objects(&self) -> Vec<Object> {
+        ObjectPool::from_iop(self.object_pool_data.clone())
+            .objects()
+            .clone()
+    }
+
+    /// The objects referenced by the copied objects that weren't copied themselves
+    pub fn referenced_objects(&self) -> Vec<Object> {
+        ObjectPool::from_iop(self.referenced_object_data.clone())
+            .objects()
+            .clone()
+    }
+
+    pub fn names(&self) -> &HashMap<u16, String> {
+        &self.names
+    }
+
+    pub fn roots(&self) -> &[ClipboardRoot] {
+        &self.roots
+    }
+}
+
+fn as_iop(objects: Vec<Object>) -> Vec<u8> {
+    let mut pool = ObjectPool::new();
+    for object in objects {
+        pool.add(object);
+    }
+    pool.as_iop()
+}
+
+/// Objects that define how other objects look or behave, which are usually shared by many objects
+fn is_attribute_object(object_type: ObjectType) -> bool {
+    matches!(
+        object_type,
+        ObjectType::FontAttributes
+            | ObjectType::LineAttributes
+            | ObjectType::FillAttributes
+            | ObjectType::InputAttributes
+            | ObjectType::ExtendedInputAttributes
+            | ObjectType::NumberVariable
+            | ObjectType::StringVariable
+            | ObjectType::Macro
+    )
+}
+
+/// Get the objects and all of their descendants, each object once and the given objects first.
+/// Attribute objects like font attributes, variables and macros are only included if
+/// `include_attributes` is true, otherwise the copies keep referring to the original ones.
+pub fn collect_subtree(
+    pool: &ObjectPool,
+    object_ids: &[ObjectId],
+    include_attributes: bool,
+) -> Vec<ObjectId> {
+    let mut collected: Vec<ObjectId> = Vec::new();
+    let mut seen: HashSet<ObjectId> = HashSet::new();
+    let mut queue: Vec<ObjectId> = object_ids.to_vec();
+    let mut index = 0;
+    while let Some(&id) = queue.get(index) {
+        index += 1;
+        let Some(object) = pool.object_by_id(id) else {
+            continue;
+        };
+        if !seen.insert(id) {
+            continue;
+        }
+        collected.push(id);
+        queue.extend(referenced_ids(object).into_iter().filter(|child| {
+            include_attributes
+                || pool
+                    .object_by_id(*child)
+                    .is_some_and(|child| !is_attribute_object(child.object_type()))
+        }));
+    }
+    collected
+}
+
+/// Get the objects referenced by the given objects that aren't among them, including the objects
+/// those reference in turn, each object once
+pub fn collect_referenced(pool: &ObjectPool, object_ids: &[ObjectId]) -> Vec<ObjectId> {
+    let referenced: Vec<ObjectId> = object_ids
+        .iter()
+        .filter_map(|id| pool.object_by_id(*id))
+        .flat_map(referenced_ids)
+        .filter(|id| !object_ids.contains(id))
+        .collect();
+    collect_subtree(pool, &referenced, true)
+        .into_iter()
+        .filter(|id| !object_ids.contains(id))
+        .collect()
+}
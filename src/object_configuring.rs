@@ -84,44 +84,65 @@ impl ConfigurableObject for Object {
     }
 }
 
-fn render_object_id(ui: &mut egui::Ui, id: &mut ObjectId, design: &EditorProject) {
-    let mut current_id = u16::from(*id);
+fn render_object_id(ui: &mut egui::Ui, id: ObjectId, design: &EditorProject) {
+    // The new ID is only applied on request, as all references to the object are updated as well
+    let edit_storage = ui.id().with("object_id_edit");
+    let mut new_id_value = ui
+        .data(|data| data.get_temp::<(ObjectId, u16)>(edit_storage))
+        .filter(|(edited_id, _)| *edited_id == id)
+        .map_or(u16::from(id), |(_, value)| value);
+
+    // Macros are referenced by events using a single byte
+    let max_id = match design.get_pool().object_by_id(id) {
+        Some(Object::Macro(_)) => u8::MAX as u16,
+        _ => 65534,
+    };
 
     ui.horizontal(|ui| {
         ui.label("Object ID:");
 
-        let widget = egui::DragValue::new(&mut current_id)
+        let widget = egui::DragValue::new(&mut new_id_value)
             .speed(1.0)
-            .range(0..=65534);
-        let resp = ui.add(widget);
+            .range(0..=max_id);
+        ui.add(widget);
 
-        let new_id = ObjectId::new(current_id).unwrap();
-
-        // Check if the new ID is already used by another object (excluding the current object)
-        let conflict = design.get_pool().object_by_id(new_id).is_some() && new_id != *id;
-
-        let conflict_storage = ui.id().with("conflict");
-        let was_conflict = ui.data(|data| data.get_temp::<u16>(conflict_storage));
+        if new_id_value == u16::from(id) {
+            ui.data_mut(|data| data.remove_temp::<(ObjectId, u16)>(edit_storage));
+            return;
+        }
+        ui.data_mut(|data| data.insert_temp(edit_storage, (id, new_id_value)));
 
-        if conflict || was_conflict.is_some_and(|id| id == current_id) {
+        let new_id = ObjectId::new(new_id_value).unwrap();
+        if design.get_pool().object_by_id(new_id).is_some() {
             ui.colored_label(egui::Color32::RED, "ID already in use!");
-
-            // Save the conflict in storage so it is still displayed next frame
-            ui.data_mut(|data| {
-                data.insert_temp(conflict_storage, u16::from(*id));
-            });
-        } else if resp.changed() || was_conflict.is_some_and(|id| id != current_id) {
-            // Remove the conflict from storage if we are actively changing the ID,
-            // or if the ID has changed (most likely another object is selected)
-            ui.data_mut(|data| {
-                data.remove_temp::<u16>(conflict_storage);
-            });
+        } else {
+            let reference_count = design.count_references_to(id);
+            if ui
+                .button("Apply")
+                .on_hover_text("Change the ID of this object and all references to it")
+                .clicked()
+            {
+                design.change_object_id(id, new_id);
+                ui.data_mut(|data| data.remove_temp::<(ObjectId, u16)>(edit_storage));
+            }
+            match reference_count {
+                1 => ui.label("1 reference will be updated"),
+                count => ui.label(format!("{} references will be updated", count)),
+            };
+            if !design
+                .get_pool()
+                .objects_by_type(ObjectType::Macro)
+                .is_empty()
+            {
+                ui.label("Object IDs in the values of macro commands are not updated")
+                    .on_hover_text(
+                        "IDs set as value by Change Numeric Value or Change Attribute commands, \
+                         e.g. of object pointers, have to be changed by hand",
+                    );
+            }
         }
-
-        if !conflict && resp.changed() {
-            design.update_object_id_for_info(*id, new_id);
-            *id = new_id;
-            design.get_mut_selected().borrow_mut().0 = Some(*id);
+        if ui.button("Cancel").clicked() {
+            ui.data_mut(|data| data.remove_temp::<(ObjectId, u16)>(edit_storage));
         }
     });
 }
@@ -518,7 +539,7 @@ fn render_add_macro_reference(
 
 impl ConfigurableObject for WorkingSet {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.background_colour, 0..=255)
                 .text("Background Colour")
@@ -552,7 +573,7 @@ impl ConfigurableObject for WorkingSet {
             design.mask_size,
             design.mask_size,
             &mut self.object_refs,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -568,7 +589,7 @@ impl ConfigurableObject for WorkingSet {
 
 impl ConfigurableObject for DataMask {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.background_colour, 0..=255)
                 .text("Background Colour")
@@ -609,7 +630,7 @@ impl ConfigurableObject for DataMask {
             design.mask_size,
             design.mask_size,
             &mut self.object_refs,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -625,7 +646,7 @@ impl ConfigurableObject for DataMask {
 
 impl ConfigurableObject for AlarmMask {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.background_colour, 0..=255)
                 .text("Background Colour")
@@ -679,7 +700,7 @@ impl ConfigurableObject for AlarmMask {
             design.mask_size,
             design.mask_size,
             &mut self.object_refs,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -695,7 +716,7 @@ impl ConfigurableObject for AlarmMask {
 
 impl ConfigurableObject for Container {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.checkbox(&mut self.hidden, "Hidden");
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
@@ -715,7 +736,7 @@ impl ConfigurableObject for Container {
             self.width,
             self.height,
             &mut self.object_refs,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -731,7 +752,7 @@ impl ConfigurableObject for Container {
 
 impl ConfigurableObject for SoftKeyMask {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.background_colour, 0..=255)
                 .text("Background Colour")
@@ -743,7 +764,7 @@ impl ConfigurableObject for SoftKeyMask {
             ui,
             design,
             &mut self.objects,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -759,7 +780,7 @@ impl ConfigurableObject for SoftKeyMask {
 
 impl ConfigurableObject for Key {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.background_colour, 0..=255)
                 .text("Background Colour")
@@ -778,7 +799,7 @@ impl ConfigurableObject for Key {
             design.mask_size,
             design.mask_size,
             &mut self.object_refs,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -794,7 +815,7 @@ impl ConfigurableObject for Key {
 
 impl ConfigurableObject for Button {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
@@ -833,14 +854,15 @@ impl ConfigurableObject for Button {
             });
         }
 
-        // TODO: check if we have VT version 4 or later
-        // ui.checkbox(&mut self.options.suppress_border, "Suppress Border");
-        // ui.checkbox(
-        //     &mut self.options.transparent_background,
-        //     "Transparent Background",
-        // );
-        // ui.checkbox(&mut self.options.disabled, "Disabled");
-        // ui.checkbox(&mut self.options.no_border, "No Border");
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.checkbox(&mut self.options.suppress_border, "Suppress Border");
+            ui.checkbox(
+                &mut self.options.transparent_background,
+                "Transparent Background",
+            );
+            ui.checkbox(&mut self.options.disabled, "Disabled");
+            ui.checkbox(&mut self.options.no_border, "No Border");
+        }
 
         ui.separator();
         ui.label("Objects:");
@@ -850,7 +872,7 @@ impl ConfigurableObject for Button {
             self.width,
             self.height,
             &mut self.object_refs,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -866,7 +888,7 @@ impl ConfigurableObject for Button {
 
 impl ConfigurableObject for InputBoolean {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.background_colour, 0..=255)
                 .text("Background Colour")
@@ -944,7 +966,7 @@ impl ConfigurableObject for InputBoolean {
 
 impl ConfigurableObject for InputString {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
                 .text("Width")
@@ -997,10 +1019,9 @@ impl ConfigurableObject for InputString {
         });
         ui.checkbox(&mut self.options.transparent, "Transparent Background");
         ui.checkbox(&mut self.options.auto_wrap, "Auto Wrap");
-        // TODO: check if we have VT version 4 or later
-        // if self.options.auto_wrap {
-        //     ui.checkbox(&mut self.options.wrap_on_hyphen, "Wrap on Hyphen");
-        // }
+        if self.options.auto_wrap && design.get_vt_version() >= VtVersion::Version4 {
+            ui.checkbox(&mut self.options.wrap_on_hyphen, "Wrap on Hyphen");
+        }
         ui.horizontal(|ui| {
             ui.label("Variable reference:");
             egui::ComboBox::from_id_salt("variable_reference")
@@ -1045,25 +1066,26 @@ impl ConfigurableObject for InputString {
                 "Right",
             );
         });
-        // TODO: check if we have VT version 4 or later
-        // ui.horizontal(|ui| {
-        //     ui.label("Vertical Justification:");
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Top,
-        //         "Top",
-        //     );
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Middle,
-        //         "Middle",
-        //     );
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Bottom,
-        //         "Bottom",
-        //     );
-        // });
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.horizontal(|ui| {
+                ui.label("Vertical Justification:");
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Top,
+                    "Top",
+                );
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Middle,
+                    "Middle",
+                );
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Bottom,
+                    "Bottom",
+                );
+            });
+        }
         if self.variable_reference.0.is_none() {
             ui.label("Initial value:");
             ui.text_edit_singleline(&mut self.value);
@@ -1082,7 +1104,7 @@ impl ConfigurableObject for InputString {
 
 impl ConfigurableObject for InputNumber {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
                 .text("Width")
@@ -1124,8 +1146,9 @@ impl ConfigurableObject for InputNumber {
             &mut self.options.display_zero_as_blank,
             "Display Zero as Blank",
         );
-        // TODO: check if we have VT version 4 or later
-        // ui.checkbox(&mut self.options.truncate, "Truncate");
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.checkbox(&mut self.options.truncate, "Truncate");
+        }
         ui.horizontal(|ui| {
             ui.label("Variable reference:");
             egui::ComboBox::from_id_salt("variable_reference")
@@ -1201,29 +1224,31 @@ impl ConfigurableObject for InputNumber {
                 "Right",
             );
         });
-        // TODO: check if we have VT version 4 or later
-        // ui.horizontal(|ui| {
-        //     ui.label("Vertical Justification:");
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Top,
-        //         "Top",
-        //     );
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Middle,
-        //         "Middle",
-        //     );
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Bottom,
-        //         "Bottom",
-        //     );
-        // });
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.horizontal(|ui| {
+                ui.label("Vertical Justification:");
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Top,
+                    "Top",
+                );
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Middle,
+                    "Middle",
+                );
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Bottom,
+                    "Bottom",
+                );
+            });
+        }
 
         ui.checkbox(&mut self.options2.enabled, "Enabled");
-        // TODO: check if we have VT version 4 or later
-        // ui.checkbox(&mut self.options2.real_time_editing, "Real Time Editing");
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.checkbox(&mut self.options2.real_time_editing, "Real Time Editing");
+        }
 
         ui.separator();
         ui.label("Macros:");
@@ -1238,7 +1263,7 @@ impl ConfigurableObject for InputNumber {
 
 impl ConfigurableObject for InputList {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
                 .text("Width")
@@ -1281,8 +1306,9 @@ impl ConfigurableObject for InputList {
         }
 
         ui.checkbox(&mut self.options.enabled, "Enabled");
-        // TODO: check if we have VT version 4 or later
-        // ui.checkbox(&mut self.options.real_time_editing, "Real Time Editing");
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.checkbox(&mut self.options.real_time_editing, "Real Time Editing");
+        }
 
         ui.separator();
         ui.label("List items:");
@@ -1290,7 +1316,7 @@ impl ConfigurableObject for InputList {
             ui,
             design,
             &mut self.list_items,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -1306,7 +1332,7 @@ impl ConfigurableObject for InputList {
 
 impl ConfigurableObject for OutputString {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
                 .text("Width")
@@ -1341,10 +1367,9 @@ impl ConfigurableObject for OutputString {
         });
         ui.checkbox(&mut self.options.transparent, "Transparent Background");
         ui.checkbox(&mut self.options.auto_wrap, "Auto Wrap");
-        // TODO: check if we have VT version 4 or later
-        // if self.options.auto_wrap {
-        //     ui.checkbox(&mut self.options.wrap_on_hyphen, "Wrap on Hyphen");
-        // }
+        if self.options.auto_wrap && design.get_vt_version() >= VtVersion::Version4 {
+            ui.checkbox(&mut self.options.wrap_on_hyphen, "Wrap on Hyphen");
+        }
         ui.horizontal(|ui| {
             ui.label("Variable reference:");
             egui::ComboBox::from_id_salt("variable_reference")
@@ -1389,25 +1414,26 @@ impl ConfigurableObject for OutputString {
                 "Right",
             );
         });
-        // TODO: check if we have VT version 4 or later
-        // ui.horizontal(|ui| {
-        //     ui.label("Vertical Justification:");
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Top,
-        //         "Top",
-        //     );
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Middle,
-        //         "Middle",
-        //     );
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Bottom,
-        //         "Bottom",
-        //     );
-        // });
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.horizontal(|ui| {
+                ui.label("Vertical Justification:");
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Top,
+                    "Top",
+                );
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Middle,
+                    "Middle",
+                );
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Bottom,
+                    "Bottom",
+                );
+            });
+        }
         if self.variable_reference.0.is_none() {
             ui.label("Initial value:");
             ui.text_edit_singleline(&mut self.value);
@@ -1425,7 +1451,7 @@ impl ConfigurableObject for OutputString {
 
 impl ConfigurableObject for OutputNumber {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
                 .text("Width")
@@ -1468,8 +1494,9 @@ impl ConfigurableObject for OutputNumber {
             &mut self.options.display_zero_as_blank,
             "Display Zero as Blank",
         );
-        // TODO: check if we have VT version 4 or later
-        // ui.checkbox(&mut self.options.truncate, "Truncate");
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.checkbox(&mut self.options.truncate, "Truncate");
+        }
         ui.horizontal(|ui| {
             ui.label("Variable reference:");
             egui::ComboBox::from_id_salt("variable_reference")
@@ -1536,25 +1563,26 @@ impl ConfigurableObject for OutputNumber {
                 "Right",
             );
         });
-        // TODO: check if we have VT version 4 or later
-        // ui.horizontal(|ui| {
-        //     ui.label("Vertical Justification:");
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Top,
-        //         "Top",
-        //     );
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Middle,
-        //         "Middle",
-        //     );
-        //     ui.radio_value(
-        //         &mut self.justification.vertical,
-        //         VerticalAlignment::Bottom,
-        //         "Bottom",
-        //     );
-        // });
+        if design.get_vt_version() >= VtVersion::Version4 {
+            ui.horizontal(|ui| {
+                ui.label("Vertical Justification:");
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Top,
+                    "Top",
+                );
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Middle,
+                    "Middle",
+                );
+                ui.radio_value(
+                    &mut self.justification.vertical,
+                    VerticalAlignment::Bottom,
+                    "Bottom",
+                );
+            });
+        }
 
         ui.separator();
         ui.label("Macros:");
@@ -1569,7 +1597,7 @@ impl ConfigurableObject for OutputNumber {
 
 impl ConfigurableObject for OutputList {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
@@ -1620,7 +1648,7 @@ impl ConfigurableObject for OutputList {
             ui,
             design,
             &mut self.list_items,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
 
         ui.separator();
@@ -1636,7 +1664,7 @@ impl ConfigurableObject for OutputList {
 
 impl ConfigurableObject for OutputLine {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.horizontal(|ui| {
             ui.label("Line Attributes:");
@@ -1707,7 +1735,7 @@ impl ConfigurableObject for OutputLine {
 
 impl ConfigurableObject for OutputRectangle {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.horizontal(|ui| {
             ui.label("Line Attributes:");
@@ -1808,7 +1836,7 @@ impl ConfigurableObject for OutputRectangle {
 
 impl ConfigurableObject for OutputEllipse {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.horizontal(|ui| {
             ui.label("Line Attributes:");
@@ -1924,7 +1952,7 @@ impl ConfigurableObject for OutputEllipse {
 
 impl ConfigurableObject for OutputPolygon {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
@@ -2072,7 +2100,7 @@ impl ConfigurableObject for OutputPolygon {
 
 impl ConfigurableObject for OutputMeter {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
@@ -2194,7 +2222,7 @@ impl ConfigurableObject for OutputMeter {
 
 impl ConfigurableObject for OutputLinearBarGraph {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
@@ -2370,7 +2398,7 @@ impl ConfigurableObject for OutputLinearBarGraph {
 
 impl ConfigurableObject for OutputArchedBarGraph {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
@@ -2568,7 +2596,7 @@ impl ConfigurableObject for OutputArchedBarGraph {
 
 impl ConfigurableObject for PictureGraphic {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.add(
             egui::Slider::new(&mut self.width, 0..=design.mask_size)
                 .text("Width")
@@ -2766,7 +2794,7 @@ impl ConfigurableObject for PictureGraphic {
 
 impl ConfigurableObject for NumberVariable {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.horizontal(|ui| {
             ui.label("Initial Value:");
@@ -2777,7 +2805,7 @@ impl ConfigurableObject for NumberVariable {
 
 impl ConfigurableObject for StringVariable {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.horizontal(|ui| {
             ui.label("Initial Value:");
@@ -2788,7 +2816,7 @@ impl ConfigurableObject for StringVariable {
 
 impl ConfigurableObject for FontAttributes {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.font_colour, 0..=255)
@@ -2796,8 +2824,9 @@ impl ConfigurableObject for FontAttributes {
                 .drag_value_speed(1.0),
         );
 
-        // let is_proportional = self.font_style.proportional; // TODO: check if we have VT version 4 or later
-        let is_proportional = false;
+        // Proportional fonts are only available in VT version 4 and later
+        let version_4_or_later = design.get_vt_version() >= VtVersion::Version4;
+        let is_proportional = version_4_or_later && self.font_style.proportional;
 
         // If proportional bit is set, font_size is proportional, otherwise non-proportional.
         if is_proportional {
@@ -2860,7 +2889,11 @@ impl ConfigurableObject for FontAttributes {
             const PROPRIETARY_RANGE_V3_AND_PRIOR: std::ops::RangeInclusive<u8> = 255..=255;
             const PROPRIETARY_RANGE_V4_AND_LATER: std::ops::RangeInclusive<u8> = 240..=255;
 
-            let range = PROPRIETARY_RANGE_V3_AND_PRIOR; // TODO: check if we have VT version 4 or later
+            let range = if version_4_or_later {
+                PROPRIETARY_RANGE_V4_AND_LATER
+            } else {
+                PROPRIETARY_RANGE_V3_AND_PRIOR
+            };
 
             let mut raw_value = match self.font_type {
                 FontType::Proprietary(v) => v,
@@ -2885,16 +2918,20 @@ impl ConfigurableObject for FontAttributes {
                 egui::ComboBox::from_id_salt("font_type")
                     .selected_text(format!("{:?}", self.font_type))
                     .show_ui(ui, |ui| {
-                        // Known fonts
-                        for value in &[
-                            FontType::Latin1,
-                            FontType::Latin9,
-                            // TODO: check if we have VT version 4 or later
-                            // FontType::Latin2,
-                            // FontType::Latin4,
-                            // FontType::Cyrillic,
-                            // FontType::Greek,
-                        ] {
+                        // Known fonts, all but Latin 1 and Latin 9 require VT version 4 or later
+                        let font_types: &[FontType] = if version_4_or_later {
+                            &[
+                                FontType::Latin1,
+                                FontType::Latin9,
+                                FontType::Latin2,
+                                FontType::Latin4,
+                                FontType::Cyrillic,
+                                FontType::Greek,
+                            ]
+                        } else {
+                            &[FontType::Latin1, FontType::Latin9]
+                        };
+                        for value in font_types {
                             if ui
                                 .selectable_label(&self.font_type == value, format!("{:?}", value))
                                 .clicked()
@@ -2915,7 +2952,18 @@ impl ConfigurableObject for FontAttributes {
         ui.checkbox(&mut self.font_style.inverted, "Inverted");
         ui.checkbox(&mut self.font_style.flashing_inverted, "Flashing Inverted");
         ui.checkbox(&mut self.font_style.flashing_hidden, "Flashing Hidden");
-        // ui.checkbox(&mut self.font_style.proportional, "Proportional"); // TODO: check if we have VT version 4 or later
+        if version_4_or_later
+            && ui
+                .checkbox(&mut self.font_style.proportional, "Proportional")
+                .changed()
+        {
+            // Switch the font size to the matching kind of size
+            self.font_size = if self.font_style.proportional {
+                FontSize::Proportional(8)
+            } else {
+                FontSize::NonProportional(NonProportionalFontSize::Px6x8)
+            };
+        }
 
         ui.separator();
         ui.label("Macros:");
@@ -2930,7 +2978,7 @@ impl ConfigurableObject for FontAttributes {
 
 impl ConfigurableObject for LineAttributes {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.line_colour, 0..=255)
@@ -2988,7 +3036,7 @@ impl ConfigurableObject for LineAttributes {
 
 impl ConfigurableObject for FillAttributes {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.label("Fill Type:").on_hover_text(
             "Select how this area should be filled:\n\
                             0 = No fill\n\
@@ -3059,7 +3107,7 @@ impl ConfigurableObject for FillAttributes {
 
 impl ConfigurableObject for InputAttributes {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.horizontal(|ui| {
             ui.label("Validation Type:");
@@ -3091,7 +3139,7 @@ impl ConfigurableObject for InputAttributes {
 
 impl ConfigurableObject for ObjectPointer {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
         ui.horizontal(|ui| {
             ui.label("Object reference:");
             egui::ComboBox::from_id_salt("object_reference")
@@ -3103,8 +3151,11 @@ impl ConfigurableObject for ObjectPointer {
                         .parent_objects(self.id)
                         .iter()
                         .flat_map(|parent_obj| {
-                            get_allowed_child_refs(parent_obj.object_type(), VtVersion::Version3)
-                                .into_iter()
+                            get_allowed_child_refs(
+                                parent_obj.object_type(),
+                                design.get_vt_version(),
+                            )
+                            .into_iter()
                         })
                         .collect();
                     for potential_child in design.get_pool().objects_by_types(&object_types) {
@@ -3179,7 +3230,7 @@ const ALLOWED_MACRO_COMMANDS: &[(u8, &str, VtVersion)] = &[
 
 impl ConfigurableObject for Macro {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.label("Macro Commands:");
         egui::Grid::new("macro_commands_grid")
@@ -3210,8 +3261,8 @@ impl ConfigurableObject for Macro {
                 .selected_text("Select command")
                 .show_ui(ui, |ui| {
                     for &(code, name, version) in ALLOWED_MACRO_COMMANDS {
-                        if version > VtVersion::Version3 {
-                            continue; // TODO: check which version pool we have
+                        if version > design.get_vt_version() {
+                            continue;
                         }
 
                         if ui
@@ -3228,7 +3279,7 @@ impl ConfigurableObject for Macro {
 
 impl ConfigurableObject for AuxiliaryFunctionType2 {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.background_colour, 0..=255)
@@ -3284,14 +3335,14 @@ impl ConfigurableObject for AuxiliaryFunctionType2 {
             design.mask_size,
             design.mask_size,
             &mut self.object_refs,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
     }
 }
 
 impl ConfigurableObject for AuxiliaryInputType2 {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.add(
             egui::Slider::new(&mut self.background_colour, 0..=255)
@@ -3346,14 +3397,14 @@ impl ConfigurableObject for AuxiliaryInputType2 {
             design.mask_size,
             design.mask_size,
             &mut self.object_refs,
-            &Self::get_allowed_child_refs(VtVersion::Version3),
+            &Self::get_allowed_child_refs(design.get_vt_version()),
         );
     }
 }
 
 impl ConfigurableObject for AuxiliaryControlDesignatorType2 {
     fn render_parameters(&mut self, ui: &mut egui::Ui, design: &EditorProject) {
-        render_object_id(ui, &mut self.id, design);
+        render_object_id(ui, self.id, design);
 
         ui.horizontal(|ui| {
             ui.label("Pointer Type:");
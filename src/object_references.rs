@@ -0,0 +1,616 @@
+//! Copyright 2024 - The Open-Agriculture Developers
+//! SPDX-License-Identifier: GPL-3.0-or-later
+//! Authors: Daan Steenbergen
+
+use std::ops::Range;
+
+use ag_iso_stack::object_pool::{
+    object::Object, object_attributes::MacroRef, vt_version::VtVersion, NullableObjectId, ObjectId,
+    ObjectRef,
+};
+
+/// A reference held by an object to one or more other objects in the pool
+pub enum Reference<'a> {
+    Id(&'a ObjectId),
+    NullableId(&'a NullableObjectId),
+    Ids(&'a Vec<ObjectId>),
+    NullableIds(&'a Vec<NullableObjectId>),
+    ObjectRefs(&'a Vec<ObjectRef>),
+    MacroRefs(&'a Vec<MacroRef>),
+    /// The commands of a macro, see `macro_command_ids` for the IDs in them
+    MacroCommands(&'a Vec<u8>),
+}
+
+/// A mutable reference held by an object to one or more other objects in the pool
+pub enum ReferenceMut<'a> {
+    Id(&'a mut ObjectId),
+    NullableId(&'a mut NullableObjectId),
+    Ids(&'a mut Vec<ObjectId>),
+    NullableIds(&'a mut Vec<NullableObjectId>),
+    ObjectRefs(&'a mut Vec<ObjectRef>),
+    MacroRefs(&'a mut Vec<MacroRef>),
+    MacroCommands(&'a mut Vec<u8>),
+}
+
+/// Call `$visit` for every attribute of `$object` that references other objects. The attributes
+/// are wrapped in `$kind`, which is either `Reference` or `ReferenceMut` depending on `$mut`.
+macro_rules! visit_references {
+    ($object:expr, $visit:ident, $kind:ident, $($mut:tt)?) => {
+        match $object {
+            Object::WorkingSet(o) => {
+                $visit($kind::Id(&$($mut)? o.active_mask));
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::DataMask(o) => {
+                $visit($kind::NullableId(&$($mut)? o.soft_key_mask));
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::AlarmMask(o) => {
+                $visit($kind::NullableId(&$($mut)? o.soft_key_mask));
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::Container(o) => {
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::SoftKeyMask(o) => {
+                $visit($kind::Ids(&$($mut)? o.objects));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::Key(o) => {
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::Button(o) => {
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::InputBoolean(o) => {
+                $visit($kind::Id(&$($mut)? o.foreground_colour));
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::InputString(o) => {
+                $visit($kind::Id(&$($mut)? o.font_attributes));
+                $visit($kind::NullableId(&$($mut)? o.input_attributes));
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::InputNumber(o) => {
+                $visit($kind::Id(&$($mut)? o.font_attributes));
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::InputList(o) => {
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::NullableIds(&$($mut)? o.list_items));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputString(o) => {
+                $visit($kind::Id(&$($mut)? o.font_attributes));
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputNumber(o) => {
+                $visit($kind::Id(&$($mut)? o.font_attributes));
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputList(o) => {
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::NullableIds(&$($mut)? o.list_items));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputLine(o) => {
+                $visit($kind::Id(&$($mut)? o.line_attributes));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputRectangle(o) => {
+                $visit($kind::Id(&$($mut)? o.line_attributes));
+                $visit($kind::NullableId(&$($mut)? o.fill_attributes));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputEllipse(o) => {
+                $visit($kind::Id(&$($mut)? o.line_attributes));
+                $visit($kind::NullableId(&$($mut)? o.fill_attributes));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputPolygon(o) => {
+                $visit($kind::Id(&$($mut)? o.line_attributes));
+                $visit($kind::NullableId(&$($mut)? o.fill_attributes));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputMeter(o) => {
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputLinearBarGraph(o) => {
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::NullableId(&$($mut)? o.target_value_variable_reference));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::OutputArchedBarGraph(o) => {
+                $visit($kind::NullableId(&$($mut)? o.variable_reference));
+                $visit($kind::NullableId(&$($mut)? o.target_value_variable_reference));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::PictureGraphic(o) => {
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::FontAttributes(o) => {
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::LineAttributes(o) => {
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::FillAttributes(o) => {
+                $visit($kind::NullableId(&$($mut)? o.fill_pattern));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::InputAttributes(o) => {
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::ObjectPointer(o) => {
+                $visit($kind::NullableId(&$($mut)? o.value));
+            }
+            Object::AuxiliaryFunctionType1(o) => {
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+            }
+            Object::AuxiliaryInputType1(o) => {
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+            }
+            Object::AuxiliaryFunctionType2(o) => {
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+            }
+            Object::AuxiliaryInputType2(o) => {
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+            }
+            Object::AuxiliaryControlDesignatorType2(o) => {
+                $visit($kind::NullableId(&$($mut)? o.auxiliary_object_id));
+            }
+            Object::WindowMask(o) => {
+                $visit($kind::Id(&$($mut)? o.name));
+                $visit($kind::NullableId(&$($mut)? o.window_title));
+                $visit($kind::NullableId(&$($mut)? o.window_icon));
+                $visit($kind::NullableIds(&$($mut)? o.objects));
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::KeyGroup(o) => {
+                $visit($kind::Id(&$($mut)? o.name));
+                $visit($kind::NullableId(&$($mut)? o.key_group_icon));
+                $visit($kind::Ids(&$($mut)? o.objects));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::GraphicsContext(o) => {
+                $visit($kind::NullableId(&$($mut)? o.font_attributes_object));
+                $visit($kind::NullableId(&$($mut)? o.line_attributes_object));
+                $visit($kind::NullableId(&$($mut)? o.fill_attributes_object));
+            }
+            Object::ObjectLabelReferenceList(o) => {
+                for label in &$($mut)? o.object_labels {
+                    $visit($kind::Id(&$($mut)? label.id));
+                    $visit($kind::NullableId(&$($mut)? label.string_variable_reference));
+                    $visit($kind::NullableId(&$($mut)? label.graphic_representation));
+                }
+            }
+            Object::ExternalObjectDefinition(o) => {
+                $visit($kind::NullableIds(&$($mut)? o.objects));
+            }
+            // The external object ID refers to an object in another working set's pool
+            Object::ExternalObjectPointer(o) => {
+                $visit($kind::NullableId(&$($mut)? o.default_object_id));
+                $visit($kind::NullableId(&$($mut)? o.external_reference_name_id));
+            }
+            Object::Animation(o) => {
+                $visit($kind::ObjectRefs(&$($mut)? o.object_refs));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::WorkingSetSpecialControls(o) => {
+                $visit($kind::NullableId(&$($mut)? o.id_of_colour_map));
+                $visit($kind::NullableId(&$($mut)? o.id_of_colour_palette));
+            }
+            Object::ScaledGraphic(o) => {
+                $visit($kind::NullableId(&$($mut)? o.value));
+                $visit($kind::MacroRefs(&$($mut)? o.macro_refs));
+            }
+            Object::Macro(o) => {
+                $visit($kind::MacroCommands(&$($mut)? o.commands));
+            }
+            Object::NumberVariable(_)
+            | Object::StringVariable(_)
+            | Object::ExtendedInputAttributes(_)
+            | Object::ColourMap(_)
+            | Object::ExternalReferenceName(_)
+            | Object::ColourPalette(_)
+            | Object::GraphicData(_) => {}
+        }
+    };
+}
+
+/// Visit every attribute of an object that references other objects in the pool.
+/// Unlike `Object::referenced_objects`, this includes attribute objects, variables and macros.
+pub fn visit_references<'a>(object: &'a Object, mut visit: impl FnMut(Reference<'a>)) {
+    visit_references!(object, visit, Reference,);
+}
+
+/// Visit every attribute of an object that references other objects in the pool, mutably
+pub fn visit_references_mut<'a>(object: &'a mut Object, mut visit: impl FnMut(ReferenceMut<'a>)) {
+    visit_references!(object, visit, ReferenceMut, mut);
+}
+
+/// Macros are referenced by the lower byte of their object ID
+fn macro_object_id(macro_ref: &MacroRef) -> Option<ObjectId> {
+    ObjectId::new(macro_ref.macro_id as u16).ok()
+}
+
+/// An object ID in the commands of a macro
+struct CommandId {
+    /// The bytes of the command the ID is part of
+    command: Range<usize>,
+    /// Position of the ID in the commands
+    position: usize,
+    /// Whether the ID is the single byte ID of a macro instead of a 16-bit object ID
+    byte: bool,
+}
+
+impl CommandId {
+    fn get(&self, commands: &[u8]) -> Option<ObjectId> {
+        let value = if self.byte {
+            commands[self.position] as u16
+        } else {
+            u16::from_le_bytes([commands[self.position], commands[self.position + 1]])
+        };
+        ObjectId::new(value).ok()
+    }
+
+    /// Write a new ID, returns false if it doesn't fit
+    fn set(&self, commands: &mut [u8], id: ObjectId) -> bool {
+        if self.byte {
+            let Ok(value) = u8::try_from(id.value()) else {
+                return false;
+            };
+            commands[self.position] = value;
+        } else {
+            commands[self.position..self.position + 2].copy_from_slice(&id.value().to_le_bytes());
+        }
+        true
+    }
+}
+
+/// Get the length of the macro command at the start of `command`, and the offsets of the object
+/// IDs in it with whether they are single byte macro IDs, see ISO 11783-6 Annex F.
+/// Values that only are an object ID for some objects, like the value of the Change Numeric Value
+/// command for an object pointer, are not included. Returns None for unknown commands and
+/// commands of which the length can't be decoded, like the Graphics Context command.
+fn macro_command_layout(command: &[u8]) -> Option<(usize, &'static [(usize, bool)])> {
+    let layout: (usize, &'static [(usize, bool)]) = match command.first()? {
+        // Select Active Working Set
+        0x90 => (9, &[]),
+        // ESC, Control Audio Signal, Set Audio Volume
+        0x92 | 0xA3 | 0xA4 => (8, &[]),
+        // Hide/Show Object, Enable/Disable Object, Select Input Object, Change Size, Change
+        // Background Colour, Change Numeric Value, Change End Point, Change Font Attributes,
+        // Change Line Attributes, Change Attribute, Change Priority, Change Polygon Point, Change
+        // Polygon Scale, Select Colour Map or Palette, Execute Extended Macro
+        0xA0 | 0xA1 | 0xA2 | 0xA6 | 0xA7 | 0xA8 | 0xA9 | 0xAA | 0xAB | 0xAF | 0xB0 | 0xB6
+        | 0xB7 | 0xBA | 0xBC => (8, &[(1, false)]),
+        // Change Child Location with the parent and the child, Change Active Mask with the
+        // working set and the mask
+        0xA5 | 0xAD => (8, &[(1, false), (3, false)]),
+        // Change Fill Attributes with the fill pattern
+        0xAC => (8, &[(1, false), (5, false)]),
+        // Change Soft Key Mask with the data or alarm mask and the soft key mask
+        0xAE => (8, &[(2, false), (4, false)]),
+        // Change List Item with the new list item
+        0xB1 => (8, &[(1, false), (4, false)]),
+        // Change String Value with the length of the string
+        0xB3 => {
+            let length = u16::from_le_bytes([*command.get(3)?, *command.get(4)?]);
+            (5 + length as usize, &[(1, false)])
+        }
+        // Change Child Position with the parent and the child
+        0xB4 => (9, &[(1, false), (3, false)]),
+        // Change Object Label with the string variable and the graphic representation
+        0xB5 => (8, &[(1, false), (3, false), (6, false)]),
+        // Lock/Unlock Mask
+        0xBD => (8, &[(2, false)]),
+        // Execute Macro
+        0xBE => (8, &[(1, true)]),
+        _ => return None,
+    };
+    Some(layout)
+}
+
+/// Get the object IDs in the commands of a macro. Decoding stops at the first command that
+/// can't be decoded, see `macro_command_layout`.
+fn macro_command_ids(commands: &[u8]) -> Vec<CommandId> {
+    let mut ids = Vec::new();
+    let mut start = 0;
+    while let Some((length, offsets)) = macro_command_layout(&commands[start..]) {
+        let command = start..start + length;
+        if command.end > commands.len() {
+            break;
+        }
+        ids.extend(offsets.iter().map(|&(offset, byte)| CommandId {
+            command: command.clone(),
+            position: start + offset,
+            byte,
+        }));
+        start = command.end;
+    }
+    ids
+}
+
+/// Remove the commands in `ranges`, which are in the order of the commands
+fn remove_commands(commands: &mut Vec<u8>, mut ranges: Vec<Range<usize>>) {
+    ranges.dedup();
+    for range in ranges.into_iter().rev() {
+        commands.drain(range);
+    }
+}
+
+/// The highest ID a Macro object can have. VT version 5 and later allow 16-bit macro IDs, but
+/// those macros can't be referenced by the events of objects, see `byte_macro_ids`.
+pub fn max_macro_id(version: VtVersion) -> u16 {
+    match version {
+        VtVersion::Version0
+        | VtVersion::Version1
+        | VtVersion::Version2
+        | VtVersion::Version3
+        | VtVersion::Version4 => u8::MAX as u16,
+        VtVersion::Version5 | VtVersion::Version6 => u16::MAX - 1,
+    }
+}
+
+/// Get the IDs of the macros an object references with a single byte, which need an ID below 256
+pub fn byte_macro_ids(object: &Object) -> Vec<ObjectId> {
+    let mut ids = Vec::new();
+    visit_references(object, |reference| match reference {
+        Reference::MacroRefs(list) => ids.extend(list.iter().filter_map(macro_object_id)),
+        Reference::MacroCommands(commands) => ids.extend(
+            macro_command_ids(commands)
+                .iter()
+                .filter(|command_id| command_id.byte)
+                .filter_map(|command_id| command_id.get(commands)),
+        ),
+        _ => {}
+    });
+    ids
+}
+
+/// Get the IDs of all objects referenced by an object, once for every reference
+pub fn referenced_ids(object: &Object) -> Vec<ObjectId> {
+    let mut ids = Vec::new();
+    visit_references(object, |reference| match reference {
+        Reference::Id(id) => ids.push(*id),
+        Reference::NullableId(id) => ids.extend(id.0),
+        Reference::Ids(list) => ids.extend(list.iter().copied()),
+        Reference::NullableIds(list) => ids.extend(list.iter().filter_map(|id| id.0)),
+        Reference::ObjectRefs(list) => ids.extend(list.iter().map(|object_ref| object_ref.id)),
+        Reference::MacroRefs(list) => ids.extend(list.iter().filter_map(macro_object_id)),
+        Reference::MacroCommands(commands) => ids.extend(
+            macro_command_ids(commands)
+                .iter()
+                .filter_map(|command_id| command_id.get(commands)),
+        ),
+    });
+    ids
+}
+
+/// Replace every referenced object ID for which `map` returns a new ID.
+/// Macro references and Execute Macro commands are removed if the new ID doesn't fit in a byte,
+/// so they never keep pointing at the old macro. Returns the number of references that were
+/// changed or removed.
+pub fn map_references(
+    object: &mut Object,
+    mut map: impl FnMut(ObjectId) -> Option<ObjectId>,
+) -> usize {
+    let mut changed = 0;
+    visit_references_mut(object, |reference| {
+        let mut map_id = |id: &mut ObjectId| {
+            if let Some(new_id) = map(*id) {
+                *id = new_id;
+                changed += 1;
+            }
+        };
+        match reference {
+            ReferenceMut::Id(id) => map_id(id),
+            ReferenceMut::NullableId(id) => id.0.iter_mut().for_each(map_id),
+            ReferenceMut::Ids(list) => list.iter_mut().for_each(map_id),
+            ReferenceMut::NullableIds(list) => list
+                .iter_mut()
+                .filter_map(|id| id.0.as_mut())
+                .for_each(map_id),
+            ReferenceMut::ObjectRefs(list) => list
+                .iter_mut()
+                .for_each(|object_ref| map_id(&mut object_ref.id)),
+            ReferenceMut::MacroRefs(list) => list.retain_mut(|macro_ref| {
+                let Some(new_id) = macro_object_id(macro_ref).and_then(&mut map) else {
+                    return true;
+                };
+                changed += 1;
+                match u8::try_from(new_id.value()) {
+                    Ok(macro_id) => {
+                        macro_ref.macro_id = macro_id;
+                        true
+                    }
+                    Err(_) => false,
+                }
+            }),
+            ReferenceMut::MacroCommands(commands) => {
+                let mut removed_commands = Vec::new();
+                for command_id in macro_command_ids(commands) {
+                    let Some(new_id) = command_id.get(commands).and_then(&mut map) else {
+                        continue;
+                    };
+                    changed += 1;
+                    if !command_id.set(commands, new_id) {
+                        removed_commands.push(command_id.command);
+                    }
+                }
+                remove_commands(commands, removed_commands);
+            }
+        }
+    });
+    changed
+}
+
+/// Replace every reference to `old_id` with `new_id`, returns the number of references changed
+pub fn replace_references(object: &mut Object, old_id: ObjectId, new_id: ObjectId) -> usize {
+    map_references(object, |id| (id == old_id).then_some(new_id))
+}
+
+/// Check if an object references the given ID with an attribute that can't be empty
+pub fn requires_reference(object: &Object, id: ObjectId) -> bool {
+    let mut required = false;
+    visit_references(object, |reference| {
+        if let Reference::Id(referenced) = reference {
+            required |= *referenced == id;
+        }
+    });
+    required
+}
+
+/// Remove every reference for which `remove` returns true. References in lists are removed from
+/// the list, except for list items that are replaced by an empty reference to keep their index.
+/// Macro commands are removed as a whole.
+/// References that can't be empty are left untouched, see `requires_reference`.
+/// Returns the number of references that were removed.
+pub fn remove_references(object: &mut Object, mut remove: impl FnMut(ObjectId) -> bool) -> usize {
+    let mut removed = 0;
+    visit_references_mut(object, |reference| {
+        let mut remove_nullable = |id: &mut NullableObjectId| {
+            if id.0.is_some_and(&mut remove) {
+                *id = NullableObjectId::NULL;
+                removed += 1;
+            }
+        };
+        match reference {
+            ReferenceMut::Id(_) => {}
+            ReferenceMut::NullableId(id) => remove_nullable(id),
+            ReferenceMut::NullableIds(list) => list.iter_mut().for_each(remove_nullable),
+            ReferenceMut::Ids(list) => {
+                let count = list.len();
+                list.retain(|id| !remove(*id));
+                removed += count - list.len();
+            }
+            ReferenceMut::ObjectRefs(list) => {
+                let count = list.len();
+                list.retain(|object_ref| !remove(object_ref.id));
+                removed += count - list.len();
+            }
+            ReferenceMut::MacroRefs(list) => {
+                let count = list.len();
+                list.retain(|macro_ref| !macro_object_id(macro_ref).is_some_and(&mut remove));
+                removed += count - list.len();
+            }
+            ReferenceMut::MacroCommands(commands) => {
+                let removed_commands: Vec<Range<usize>> = macro_command_ids(commands)
+                    .into_iter()
+                    .filter(|command_id| command_id.get(commands).is_some_and(&mut remove))
+                    .map(|command_id| command_id.command)
+                    .collect();
+                removed += removed_commands.len();
+                remove_commands(commands, removed_commands);
+            }
+        }
+    });
+    removed
+}
+
+/// Get the child objects and their offsets of an object that positions its children
+pub fn object_refs(object: &Object) -> Option<&Vec<ObjectRef>> {
+    let mut object_refs = None;
+    visit_references(object, |reference| {
+        if let Reference::ObjectRefs(list) = reference {
+            object_refs = Some(list);
+        }
+    });
+    object_refs
+}
+
+/// Get the child objects and their offsets of an object that positions its children, mutably
+pub fn object_refs_mut(object: &mut Object) -> Option<&mut Vec<ObjectRef>> {
+    let mut object_refs = None;
+    visit_references_mut(object, |reference| {
+        if let ReferenceMut::ObjectRefs(list) = reference {
+            object_refs = Some(list);
+        }
+    });
+    object_refs
+}
+
+#[cfg(test)]
+mod tests {
+    use ag_iso_stack::object_pool::object::Macro;
+
+    use super::*;
+
+    fn id(value: u16) -> ObjectId {
+        ObjectId::new(value).unwrap()
+    }
+
+    fn macro_object(commands: &[&[u8]]) -> Object {
+        Object::Macro(Macro {
+            id: id(1),
+            commands: commands.concat(),
+        })
+    }
+
+    const HIDE_OBJECT_10: &[u8] = &[0xA0, 10, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF];
+    const EXECUTE_MACRO_5: &[u8] = &[0xBE, 5, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+    const CHANGE_ACTIVE_MASK_TO_20: &[u8] = &[0xAD, 0, 0, 20, 0, 0xFF, 0xFF, 0xFF];
+
+    #[test]
+    fn macro_command_ids_are_referenced() {
+        let object = macro_object(&[HIDE_OBJECT_10, EXECUTE_MACRO_5, CHANGE_ACTIVE_MASK_TO_20]);
+        assert_eq!(referenced_ids(&object), vec![id(10), id(5), id(0), id(20)]);
+        assert_eq!(byte_macro_ids(&object), vec![id(5)]);
+    }
+
+    #[test]
+    fn macro_command_ids_are_mapped() {
+        let mut object = macro_object(&[HIDE_OBJECT_10, EXECUTE_MACRO_5]);
+        assert_eq!(
+            map_references(&mut object, |old| (old == id(10)).then_some(id(300))),
+            1
+        );
+        assert_eq!(
+            object,
+            macro_object(&[
+                &[0xA0, 0x2C, 0x01, 0, 0xFF, 0xFF, 0xFF, 0xFF],
+                EXECUTE_MACRO_5
+            ])
+        );
+
+        // A macro ID that doesn't fit in the Execute Macro command removes the command
+        assert_eq!(
+            map_references(&mut object, |old| (old == id(5)).then_some(id(256))),
+            1
+        );
+        assert_eq!(referenced_ids(&object), vec![id(300)]);
+    }
+
+    #[test]
+    fn macro_commands_with_removed_ids_are_removed() {
+        let mut object = macro_object(&[CHANGE_ACTIVE_MASK_TO_20, HIDE_OBJECT_10]);
+        assert_eq!(
+            remove_references(&mut object, |removed| removed == id(20)),
+            1
+        );
+        assert_eq!(object, macro_object(&[HIDE_OBJECT_10]));
+        assert!(!requires_reference(&object, id(10)));
+    }
+
+    #[test]
+    fn macro_decoding_stops_at_unknown_commands() {
+        let object = macro_object(&[HIDE_OBJECT_10, &[0x01, 20, 0], HIDE_OBJECT_10]);
+        assert_eq!(referenced_ids(&object), vec![id(10)]);
+        let truncated = macro_object(&[&HIDE_OBJECT_10[..4]]);
+        assert!(referenced_ids(&truncated).is_empty());
+    }
+}
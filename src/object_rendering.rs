@@ -2,15 +2,20 @@
 //! SPDX-License-Identifier: GPL-3.0-or-later
 //! Authors: Daan Steenbergen
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::Hash;
-use std::hash::Hasher;
+use std::f32::consts::TAU;
 use std::ops::Sub;
+use std::sync::Arc;
+use std::time::Duration;
 
 use ag_iso_stack::object_pool::object::*;
+use ag_iso_stack::object_pool::object_attributes::Alignment;
+use ag_iso_stack::object_pool::object_attributes::AxisOrientation;
+use ag_iso_stack::object_pool::object_attributes::BarGraphType;
 use ag_iso_stack::object_pool::object_attributes::ButtonState;
+use ag_iso_stack::object_pool::object_attributes::DeflectionDirection;
 use ag_iso_stack::object_pool::object_attributes::FontSize;
 use ag_iso_stack::object_pool::object_attributes::FormatType;
+use ag_iso_stack::object_pool::object_attributes::GrowDirection;
 use ag_iso_stack::object_pool::object_attributes::HorizontalAlignment;
 use ag_iso_stack::object_pool::object_attributes::LineDirection;
 use ag_iso_stack::object_pool::object_attributes::PictureGraphicFormat;
@@ -18,6 +23,7 @@ use ag_iso_stack::object_pool::object_attributes::Point;
 use ag_iso_stack::object_pool::object_attributes::VerticalAlignment;
 use ag_iso_stack::object_pool::vt_version::VtVersion;
 use ag_iso_stack::object_pool::Colour;
+use ag_iso_stack::object_pool::NullableObjectId;
 use ag_iso_stack::object_pool::ObjectPool;
 use ag_iso_stack::object_pool::ObjectRef;
 use eframe::egui;
@@ -27,8 +33,11 @@ use eframe::egui::FontId;
 use eframe::egui::TextWrapMode;
 use eframe::egui::TextureHandle;
 use eframe::egui::TextureId;
+use eframe::egui::TextureOptions;
 use eframe::egui::UiBuilder;
 
+use crate::vt_font::{TextStyle, VtFont};
+
 pub trait RenderableObject {
     fn render(&self, ui: &mut egui::Ui, pool: &ObjectPool, position: Point<i16>);
 }
@@ -43,7 +52,7 @@ impl RenderableObject for Object {
             Object::DataMask(o) => o.render(ui, pool, position),
             Object::AlarmMask(o) => o.render(ui, pool, position),
             Object::Container(o) => o.render(ui, pool, position),
-            Object::SoftKeyMask(o) => (),
+            Object::SoftKeyMask(o) => o.render(ui, pool, position),
             Object::Key(o) => o.render(ui, pool, position),
             Object::Button(o) => o.render(ui, pool, position),
             Object::InputBoolean(o) => o.render(ui, pool, position),
@@ -141,6 +150,355 @@ fn render_object_refs(ui: &mut egui::Ui, pool: &ObjectPool, object_refs: &Vec<Ob
     }
 }
 
+/// The paint for the inside of a shape
+enum ShapeFill {
+    Colour(Color32),
+    /// A picture graphic tiled from the origin, which is the top left corner of the shape object
+    Pattern {
+        texture_id: TextureId,
+        size: egui::Vec2,
+        origin: egui::Pos2,
+    },
+}
+
+/// Get the paint to fill a shape with according to its fill attributes, or `None` if the shape
+/// should not be filled at all. Fill type 1 uses the line colour, 2 the fill colour and 3 the fill
+/// pattern, which falls back to the fill colour if there is no valid pattern.
+fn shape_fill(
+    ui: &egui::Ui,
+    pool: &ObjectPool,
+    fill_attributes: &FillAttributes,
+    line_attributes: &LineAttributes,
+    origin: egui::Pos2,
+) -> Option<ShapeFill> {
+    match fill_attributes.fill_type {
+        0 => None,
+        1 => Some(ShapeFill::Colour(
+            pool.color_by_index(line_attributes.line_colour).convert(),
+        )),
+        2 => Some(ShapeFill::Colour(
+            pool.color_by_index(fill_attributes.fill_colour).convert(),
+        )),
+        _ => {
+            let pattern = match fill_attributes.fill_pattern.into() {
+                Some(id) => match pool.object_by_id(id) {
+                    Some(Object::PictureGraphic(picture)) => Some(picture),
+                    _ => None,
+                },
+                None => None,
+            };
+            match pattern.and_then(|p| Some((p, picture_graphic_texture(ui, pool, p)?))) {
+                Some((picture, texture_id)) => Some(ShapeFill::Pattern {
+                    texture_id,
+                    size: egui::vec2(picture.actual_width as f32, picture.actual_height as f32),
+                    origin,
+                }),
+                None => Some(ShapeFill::Colour(
+                    pool.color_by_index(fill_attributes.fill_colour).convert(),
+                )),
+            }
+        }
+    }
+}
+
+/// Calculate the horizontal spans that cover the inside of a polygon using the even-odd rule.
+/// Each span is one point high, which allows filling non-convex and self-intersecting polygons.
+fn polygon_spans(points: &[egui::Pos2]) -> Vec<egui::Rect> {
+    let mut spans = Vec::new();
+    if points.len() < 3 {
+        return spans;
+    }
+
+    let min_y = points
+        .iter()
+        .map(|p| p.y)
+        .fold(f32::INFINITY, f32::min)
+        .floor();
+    let max_y = points
+        .iter()
+        .map(|p| p.y)
+        .fold(f32::NEG_INFINITY, f32::max)
+        .ceil();
+
+    let mut crossings: Vec<f32> = Vec::new();
+    let mut y = min_y;
+    while y < max_y {
+        // Sample each row in the middle to avoid ambiguities at the vertices
+        let scan_y = y + 0.5;
+        crossings.clear();
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            if (a.y <= scan_y) != (b.y <= scan_y) {
+                crossings.push(a.x + (scan_y - a.y) / (b.y - a.y) * (b.x - a.x));
+            }
+        }
+        crossings.sort_by(|a, b| a.total_cmp(b));
+
+        for pair in crossings.chunks_exact(2) {
+            spans.push(egui::Rect::from_min_max(
+                egui::pos2(pair[0], y),
+                egui::pos2(pair[1], y + 1.0),
+            ));
+        }
+        y += 1.0;
+    }
+    spans
+}
+
+/// Fill an arbitrary polygon. The egui tessellator only supports convex polygons, hence we build
+/// the mesh ourselves from the spans of the polygon.
+fn fill_polygon(painter: &egui::Painter, points: &[egui::Pos2], fill: &ShapeFill) {
+    fill_rects(painter, polygon_spans(points), fill);
+}
+
+/// Fill the given rects, a pattern is tiled seamlessly across all of them.
+fn fill_rects(painter: &egui::Painter, rects: Vec<egui::Rect>, fill: &ShapeFill) {
+    let mut mesh = match fill {
+        ShapeFill::Colour(_) => egui::Mesh::default(),
+        ShapeFill::Pattern { texture_id, .. } => egui::Mesh::with_texture(*texture_id),
+    };
+    for rect in rects {
+        match fill {
+            ShapeFill::Colour(colour) => mesh.add_colored_rect(rect, *colour),
+            ShapeFill::Pattern { size, origin, .. } => {
+                // The texture repeats, so the UV coordinates just continue beyond a single tile
+                let uv = egui::Rect::from_min_max(
+                    ((rect.min - *origin) / *size).to_pos2(),
+                    ((rect.max - *origin) / *size).to_pos2(),
+                );
+                mesh.add_rect_with_uv(rect, uv, Color32::WHITE);
+            }
+        }
+    }
+    if !mesh.is_empty() {
+        painter.add(egui::Shape::mesh(mesh));
+    }
+}
+
+/// Draw a line through the given points using the colour, width and line art of the line
+/// attributes. The line is drawn like a terminal moves its square paintbrush of line width along
+/// the points, so the ends of open lines extend half the line width beyond the first and last point.
+fn stroke_path(
+    painter: &egui::Painter,
+    pool: &ObjectPool,
+    line_attributes: &LineAttributes,
+    mut points: Vec<egui::Pos2>,
+    closed: bool,
+) {
+    if line_attributes.line_width == 0 || line_attributes.line_art == 0 || points.len() < 2 {
+        return;
+    }
+
+    let stroke = egui::Stroke::new(
+        line_attributes.line_width,
+        pool.color_by_index(line_attributes.line_colour).convert(),
+    );
+
+    let half_width = line_attributes.line_width as f32 / 2.0;
+    if line_attributes.line_art == u16::MAX {
+        if closed {
+            painter.add(egui::Shape::closed_line(points, stroke));
+        } else {
+            extend_line_ends(&mut points, half_width);
+            painter.add(egui::Shape::line(points, stroke));
+        }
+        return;
+    }
+
+    if closed {
+        points.push(points[0]);
+    }
+    for mut dash in line_art_dashes(&points, line_attributes.line_art) {
+        // A dash already covers the pixels of its bits, the paintbrush adds the rest of its width
+        extend_line_ends(&mut dash, half_width - 0.5);
+        painter.add(egui::Shape::line(dash, stroke));
+    }
+}
+
+/// Split a path into the dashes drawn by a 16-bit line art pattern. Each bit of the pattern covers
+/// one pixel along the path, starting with the most significant bit, and the pattern continues
+/// around the corners of the path.
+fn line_art_dashes(points: &[egui::Pos2], line_art: u16) -> Vec<Vec<egui::Pos2>> {
+    let mut dashes = Vec::new();
+    let mut dash: Vec<egui::Pos2> = Vec::new();
+    let mut pixel: u32 = 0;
+    let mut travelled = 0.0;
+
+    for segment in points.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let length = a.distance(b);
+        // Whether the last point of the dash lies on this segment, so it can be moved instead of
+        // adding a new point for every pixel
+        let mut on_segment = false;
+        let mut t = 0.0;
+        while t < length {
+            let pixel_end = (pixel + 1) as f32 - travelled;
+            let step_end = pixel_end.min(length);
+
+            if line_art & (0x8000 >> (pixel % 16)) != 0 {
+                let end = a.lerp(b, step_end / length);
+                if dash.is_empty() {
+                    dash.push(a.lerp(b, t / length));
+                    dash.push(end);
+                } else if on_segment {
+                    *dash.last_mut().unwrap() = end;
+                } else {
+                    dash.push(end);
+                }
+                on_segment = true;
+            } else if !dash.is_empty() {
+                dashes.push(std::mem::take(&mut dash));
+            }
+
+            if pixel_end <= length {
+                pixel += 1;
+            }
+            t = step_end;
+        }
+        travelled += length;
+    }
+
+    if !dash.is_empty() {
+        dashes.push(dash);
+    }
+    dashes
+}
+
+/// Extend both ends of a line in its direction, to get square line ends. A line without length is
+/// extended horizontally, so it is drawn as a square dot.
+fn extend_line_ends(points: &mut [egui::Pos2], distance: f32) {
+    let n = points.len();
+    if n < 2 {
+        return;
+    }
+
+    let mut start_direction = (points[0] - points[1]).normalized();
+    let mut end_direction = (points[n - 1] - points[n - 2]).normalized();
+    if start_direction == egui::Vec2::ZERO || end_direction == egui::Vec2::ZERO {
+        start_direction = egui::vec2(-1.0, 0.0);
+        end_direction = egui::vec2(1.0, 0.0);
+    }
+    points[0] += start_direction * distance;
+    points[n - 1] += end_direction * distance;
+}
+
+/// Calculate the point on the ellipse that fits in `rect` at the given angle. The angle is in
+/// radians, measured counter-clockwise from the positive x-axis.
+fn ellipse_point(rect: egui::Rect, angle: f32) -> egui::Pos2 {
+    let centre = rect.center();
+    let radius = rect.size() / 2.0;
+    egui::pos2(
+        centre.x + radius.x * angle.cos(),
+        centre.y - radius.y * angle.sin(),
+    )
+}
+
+/// Calculate points on the ellipse that fits in `rect`, starting at `start` and sweeping `sweep`
+/// radians. A positive sweep goes counter-clockwise.
+fn ellipse_sweep_points(rect: egui::Rect, start: f32, sweep: f32) -> Vec<egui::Pos2> {
+    // Roughly one segment for every two points along the arc
+    let radius = rect.width().max(rect.height()) / 2.0;
+    let segments = ((radius * sweep.abs() / 2.0).ceil() as usize).clamp(4, 256);
+    (0..=segments)
+        .map(|i| ellipse_point(rect, start + sweep * i as f32 / segments as f32))
+        .collect()
+}
+
+/// Calculate points on the ellipse that fits in `rect`, going counter-clockwise from `start` to
+/// `end`. The angles are in radians, measured from the positive x-axis.
+fn ellipse_arc_points(rect: egui::Rect, start: f32, end: f32) -> Vec<egui::Pos2> {
+    let mut sweep = end - start;
+    if sweep <= 0.0 {
+        sweep += TAU;
+    }
+    ellipse_sweep_points(rect, start, sweep)
+}
+
+/// Meters and arched bar graphs define their arc by a start and end angle in units of 2 degrees,
+/// measured counter-clockwise from the positive x-axis, and the direction in which the value
+/// deflects. Returns the start angle and the signed sweep of the arc in radians, where a positive
+/// sweep is counter-clockwise. Equal start and end angles result in a full circle.
+fn deflection_arc(start_angle: u8, end_angle: u8, direction: DeflectionDirection) -> (f32, f32) {
+    let start = (start_angle as f32 * 2.0).to_radians();
+    let end = (end_angle as f32 * 2.0).to_radians();
+
+    let mut sweep = match direction {
+        DeflectionDirection::AntiClockwise => (end - start).rem_euclid(TAU),
+        DeflectionDirection::Clockwise => (start - end).rem_euclid(TAU),
+    };
+    if sweep == 0.0 {
+        sweep = TAU;
+    }
+
+    match direction {
+        DeflectionDirection::AntiClockwise => (start, sweep),
+        DeflectionDirection::Clockwise => (start, -sweep),
+    }
+}
+
+/// Get the value of the referenced NumberVariable, or the given value if there is no (valid)
+/// variable reference.
+pub fn resolve_number_value(
+    pool: &ObjectPool,
+    variable_reference: NullableObjectId,
+    value: u32,
+) -> u32 {
+    if let Some(var_id) = variable_reference.into() {
+        match pool.object_by_id(var_id) {
+            Some(Object::NumberVariable(num_var)) => num_var.value,
+            _ => value,
+        }
+    } else {
+        value
+    }
+}
+
+/// Calculate where `value` lies between `min` and `max`, clamped to the range 0.0 to 1.0.
+fn value_fraction(value: u32, min: u32, max: u32) -> f32 {
+    if max > min {
+        ((value as f32 - min as f32) / (max - min) as f32).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Calculate the fraction along the axis of a meter or bar graph for the tick with `index`. The
+/// first and last tick are placed at both ends of the axis.
+fn tick_fraction(index: u8, nr_of_ticks: u8) -> f32 {
+    if nr_of_ticks > 1 {
+        index as f32 / (nr_of_ticks - 1) as f32
+    } else {
+        0.0
+    }
+}
+
+/// Render the item at `index` of an input or output list. The item is drawn at the top left corner
+/// of the list and clipped to the list area, NULL items and indexes out of range render nothing.
+fn render_list_item(
+    ui: &mut egui::Ui,
+    pool: &ObjectPool,
+    rect: egui::Rect,
+    list_items: &[NullableObjectId],
+    index: u32,
+) {
+    let item = match list_items.get(index as usize) {
+        Some(item) => *item,
+        None => return,
+    };
+
+    if let Some(id) = item.into() {
+        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
+            ui.set_clip_rect(rect.intersect(ui.clip_rect()));
+            match pool.object_by_id(id) {
+                Some(obj) => obj.render(ui, pool, Point { x: 0, y: 0 }),
+                None => {
+                    ui.colored_label(Color32::RED, format!("Missing list item: {:?}", id));
+                }
+            }
+        });
+    }
+}
+
 impl RenderableObject for WorkingSet {
     fn render(&self, ui: &mut egui::Ui, pool: &ObjectPool, _: Point<i16>) {
         if !self.selectable {
@@ -200,9 +558,24 @@ impl RenderableObject for Container {
     }
 }
 
+/// Width of the border on each side of a button
+const BUTTON_BORDER_WIDTH: f32 = 4.0;
+
+/// Get the face of a button that covers `rect`, which is where its child objects are shown.
+/// According to the standard the face is 8 pixels smaller in width and height than the button,
+/// unless the button has no border (VT version 4 and later). The border is VT proprietary, we
+/// assume it is evenly distributed over both sides.
+pub fn button_face_rect(button: &Button, rect: egui::Rect, vt_version: VtVersion) -> egui::Rect {
+    if vt_version >= VtVersion::Version4 && button.options.no_border {
+        rect
+    } else {
+        rect.shrink(BUTTON_BORDER_WIDTH)
+    }
+}
+
 impl RenderableObject for Button {
     fn render(&self, ui: &mut egui::Ui, pool: &ObjectPool, position: Point<i16>) {
-        let vt_version = VtVersion::Version3;
+        let vt_version = vt_version(ui.ctx());
 
         let rect = create_relative_rect(
             ui,
@@ -231,21 +604,7 @@ impl RenderableObject for Button {
             false
         };
 
-        // Compute the face rectangle based on border settings
-        // According to the standard:
-        // - If no_border = true: Face area = entire area (no border space).
-        // - If no_border = false: Face is 8 pixels smaller in width and height.
-        //
-        // The border is a VT proprietary 8-pixel area, but we must reduce face size accordingly.
-        // Let's assume a uniform distribution of that 8-pixel shrinkage (4 pixels on each side).
-        const BORDER_WIDTH: f32 = 4.0;
-        let face_rect = if no_border {
-            rect
-        } else {
-            // Face is area minus 8 pixels in width and height.
-            // We'll just evenly shrink by 4 pixels on each side.
-            rect.shrink(BORDER_WIDTH)
-        };
+        let face_rect = button_face_rect(self, rect, vt_version);
 
         let response = ui.interact(
             face_rect,
@@ -289,7 +648,7 @@ impl RenderableObject for Button {
             ui.painter().rect_stroke(
                 rect,
                 0.0,
-                egui::Stroke::new(BORDER_WIDTH, border_color),
+                egui::Stroke::new(BUTTON_BORDER_WIDTH, border_color),
                 egui::StrokeKind::Inside,
             );
         }
@@ -362,6 +721,294 @@ impl RenderableObject for InputBoolean {
     }
 }
 
+fn vt_version_id() -> egui::Id {
+    egui::Id::new("vt_version")
+}
+
+/// The VT version objects are rendered for, version 3 unless set otherwise
+pub fn vt_version(ctx: &egui::Context) -> VtVersion {
+    ctx.data(|data| data.get_temp(vt_version_id()))
+        .unwrap_or(VtVersion::Version3)
+}
+
+/// Set the VT version objects are rendered for, this enables the attributes of later versions
+pub fn set_vt_version(ctx: &egui::Context, version: VtVersion) {
+    ctx.data_mut(|data| data.insert_temp(vt_version_id(), version));
+}
+
+fn pool_revision_id() -> egui::Id {
+    egui::Id::new("pool_revision")
+}
+
+/// The revision of the pool objects are rendered from, see `set_pool_revision`
+fn pool_revision(ctx: &egui::Context) -> u64 {
+    ctx.data(|data| data.get_temp(pool_revision_id()))
+        .unwrap_or_default()
+}
+
+/// Set the revision of the pool objects are rendered from, which must change whenever the pool
+/// changes. Decoded pictures are cached until the revision changes.
+pub fn set_pool_revision(ctx: &egui::Context, revision: u64) {
+    ctx.data_mut(|data| data.insert_temp(pool_revision_id(), revision));
+}
+
+/// Flashing text switches between its normal and alternate state at this interval, in seconds
+const FLASH_INTERVAL: f64 = 0.5;
+
+fn flashing_frozen_id() -> egui::Id {
+    egui::Id::new("flashing_frozen")
+}
+
+/// Whether flashing text is frozen in its normal state in the preview
+pub fn flashing_frozen(ctx: &egui::Context) -> bool {
+    ctx.data(|data| data.get_temp(flashing_frozen_id()).unwrap_or(false))
+}
+
+/// Freeze or animate flashing text in the preview
+pub fn set_flashing_frozen(ctx: &egui::Context, frozen: bool) {
+    ctx.data_mut(|data| data.insert_temp(flashing_frozen_id(), frozen));
+}
+
+/// Whether flashing text is currently in its alternate state. Requests a repaint for the next
+/// switch, so the preview keeps animating.
+fn flash_phase(ui: &egui::Ui) -> bool {
+    if flashing_frozen(ui.ctx()) {
+        return false;
+    }
+    let time = ui.input(|input| input.time);
+    ui.ctx().request_repaint_after(Duration::from_secs_f64(
+        FLASH_INTERVAL - time % FLASH_INTERVAL,
+    ));
+    (time / FLASH_INTERVAL) as u64 % 2 == 1
+}
+
+/// Resolve how to paint text with the font attributes on top of the given background colour.
+/// Returns `None` while flashing hidden text is hidden.
+fn text_style(
+    ui: &egui::Ui,
+    pool: &ObjectPool,
+    font_attributes: &FontAttributes,
+    background_colour: Color32,
+) -> Option<TextStyle> {
+    let font_style = &font_attributes.font_style;
+    let alternate = (font_style.flashing_inverted || font_style.flashing_hidden) && flash_phase(ui);
+    if font_style.flashing_hidden && alternate {
+        return None;
+    }
+
+    // Inverted text swaps the font and background colour
+    let font_colour = pool.color_by_index(font_attributes.font_colour).convert();
+    let inverted = font_style.inverted != (font_style.flashing_inverted && alternate);
+    Some(TextStyle {
+        colour: if inverted {
+            background_colour
+        } else {
+            font_colour
+        },
+        background: inverted.then_some(font_colour),
+        bold: font_style.bold,
+        italic: font_style.italic,
+        underlined: font_style.underlined,
+        crossed_out: font_style.crossed_out,
+    })
+}
+
+/// Lay out proportional text with the colour and decorations of the text style
+fn layout_styled_text(
+    ui: &egui::Ui,
+    text: String,
+    font_id: FontId,
+    style: &TextStyle,
+    wrap_width: f32,
+) -> Arc<egui::Galley> {
+    let decoration = egui::Stroke::new((font_id.size / 12.0).max(1.0).round(), style.colour);
+    let format = egui::TextFormat {
+        font_id,
+        color: style.colour,
+        italics: style.italic,
+        underline: if style.underlined {
+            decoration
+        } else {
+            egui::Stroke::NONE
+        },
+        strikethrough: if style.crossed_out {
+            decoration
+        } else {
+            egui::Stroke::NONE
+        },
+        ..Default::default()
+    };
+    let mut job = egui::text::LayoutJob::single_section(text, format);
+    job.wrap.max_width = wrap_width;
+    ui.fonts(|fonts| fonts.layout_job(job))
+}
+
+/// Paint text laid out by `layout_styled_text`. Inverted text gets its background behind the
+/// text, bold text is painted a second time one pixel to the right.
+fn paint_styled_text(ui: &egui::Ui, pos: egui::Pos2, galley: Arc<egui::Galley>, style: &TextStyle) {
+    if let Some(background) = style.background {
+        ui.painter().rect_filled(
+            egui::Rect::from_min_size(pos, galley.size()),
+            0.0,
+            background,
+        );
+    }
+    if style.bold {
+        ui.painter()
+            .galley(pos + egui::vec2(1.0, 0.0), galley.clone(), style.colour);
+    }
+    ui.painter().galley(pos, galley, style.colour);
+}
+
+/// The attributes of an OutputString or InputString that determine how its text is shown
+struct StringField<'a> {
+    value: String,
+    font_attributes: &'a FontAttributes,
+    background_colour: u8,
+    transparent: bool,
+    auto_wrap: bool,
+    wrap_on_hyphen: bool,
+    justification: Alignment,
+}
+
+/// Render the text of a string field in `rect`
+fn render_string(ui: &mut egui::Ui, pool: &ObjectPool, rect: egui::Rect, field: StringField) {
+    let font_attributes = field.font_attributes;
+    let background_colour = pool.color_by_index(field.background_colour).convert();
+    let auto_wrap = field.auto_wrap;
+
+    // Wrapping on hyphens is only available in VT version 4 and later. Note: egui always treats
+    // hyphens as break opportunities, so this only affects non-proportional fonts.
+    let wrap_on_hyphen = field.wrap_on_hyphen && vt_version(ui.ctx()) >= VtVersion::Version4;
+
+    // According to the specification, we need to handle control characters (CR, LF) as line breaks.
+    // We'll normalize all line endings to '\n'.
+    let text_value = field
+        .value
+        .replace("\r\n", "\n")
+        .replace("\n\r", "\n")
+        .replace('\r', "\n")
+        .replace('\x0a', "\n");
+
+    // Apply space trimming rules based on horizontal justification:
+    // - Left justification: no trimming of leading spaces (for the first line), trailing spaces remain as is.
+    // - Middle justification: remove leading and trailing spaces on each line.
+    // - Right justification: remove trailing spaces on each line.
+    let mut lines: Vec<&str> = text_value.split('\n').collect();
+    for (line_number, line) in lines.iter_mut().enumerate() {
+        match field.justification.horizontal {
+            HorizontalAlignment::Left => {
+                // Per ISO rules, if auto-wrapping is enabled, leading spaces on wrapped lines might be removed.
+                if auto_wrap && line_number > 0 {
+                    // Remove leading spaces
+                    *line = line.trim_start();
+                }
+            }
+            HorizontalAlignment::Middle => {
+                // Remove both leading and trailing spaces
+                *line = line.trim();
+            }
+            HorizontalAlignment::Right => {
+                // Remove trailing spaces only
+                *line = line.trim_end();
+            }
+            HorizontalAlignment::Reserved => {
+                ui.colored_label(
+                    Color32::RED,
+                    "Configuration incorrect: horizontal alignment is set to Reserved",
+                );
+                return;
+            }
+        }
+    }
+
+    let processed_text = lines.join("\n");
+
+    if !field.transparent {
+        ui.painter().rect_filled(rect, 0.0, background_colour);
+    }
+
+    // Resolve the colours and decorations from the font style, flashing text may be hidden
+    let Some(style) = text_style(ui, pool, font_attributes, background_colour) else {
+        return;
+    };
+
+    let font_height = match font_attributes.font_size {
+        FontSize::NonProportional(size) => {
+            // Non-proportional fonts are drawn from bitmaps, so wrapping and truncation match
+            // a real terminal
+            let font = VtFont::new(font_attributes.font_type, size);
+            let lines = font.layout(
+                &processed_text,
+                auto_wrap.then_some(rect.width()),
+                wrap_on_hyphen,
+            );
+            font.paint(ui, rect, &lines, field.justification, &style);
+            return;
+        }
+        FontSize::Proportional(height) => height as f32,
+    };
+    let font_family = egui::FontFamily::Proportional;
+
+    let wrap_width = if auto_wrap {
+        rect.width()
+    } else {
+        f32::INFINITY
+    };
+
+    let galley = layout_styled_text(
+        ui,
+        processed_text,
+        FontId::new(font_height, font_family),
+        &style,
+        wrap_width,
+    );
+
+    let text_size = galley.size();
+
+    let mut paint_pos = rect.min;
+
+    match field.justification.horizontal {
+        HorizontalAlignment::Left => {
+            paint_pos.x = rect.min.x;
+        }
+        HorizontalAlignment::Middle => {
+            paint_pos.x = rect.center().x - (text_size.x * 0.5);
+        }
+        HorizontalAlignment::Right => {
+            paint_pos.x = rect.max.x - text_size.x;
+        }
+        HorizontalAlignment::Reserved => {
+            ui.colored_label(
+                Color32::RED,
+                "Configuration incorrect: horizontal alignment is set to Reserved",
+            );
+            return;
+        }
+    };
+
+    match field.justification.vertical {
+        VerticalAlignment::Top => {
+            paint_pos.y = rect.min.y;
+        }
+        VerticalAlignment::Middle => {
+            paint_pos.y = rect.center().y - (text_size.y * 0.5);
+        }
+        VerticalAlignment::Bottom => {
+            paint_pos.y = rect.max.y - text_size.y;
+        }
+        VerticalAlignment::Reserved => {
+            ui.colored_label(
+                Color32::RED,
+                "Configuration incorrect: vertical alignment is set to Reserved",
+            );
+            return;
+        }
+    };
+
+    paint_styled_text(ui, paint_pos, galley, &style);
+}
+
 impl RenderableObject for InputString {
     fn render(&self, ui: &mut egui::Ui, pool: &ObjectPool, position: Point<i16>) {
         let rect = create_relative_rect(
@@ -370,9 +1017,45 @@ impl RenderableObject for InputString {
             egui::Vec2::new(self.width() as f32, self.height() as f32),
         );
 
-        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
-            ui.colored_label(Color32::RED, "InputString not implemented");
-        });
+        let font_attributes = match pool.object_by_id(self.font_attributes) {
+            Some(Object::FontAttributes(f)) => f,
+            _ => {
+                ui.colored_label(
+                    Color32::RED,
+                    format!("Missing font attributes: {:?}", self.font_attributes),
+                );
+                return;
+            }
+        };
+
+        let value = if let Some(variable_reference_id) = self.variable_reference.into() {
+            match pool.object_by_id(variable_reference_id) {
+                Some(Object::StringVariable(s)) => s.value.clone(),
+                _ => self.value.clone(),
+            }
+        } else {
+            self.value.clone()
+        };
+
+        let field = StringField {
+            value,
+            font_attributes,
+            background_colour: self.background_colour,
+            transparent: self.options.transparent,
+            auto_wrap: self.options.auto_wrap,
+            wrap_on_hyphen: self.options.wrap_on_hyphen,
+            justification: self.justification,
+        };
+        render_string(ui, pool, rect, field);
+
+        // If disabled, overlay a semi-transparent layer
+        if !self.enabled {
+            ui.painter().rect_filled(
+                rect,
+                0.0,
+                egui::Color32::from_rgba_premultiplied(128, 128, 128, 100),
+            );
+        }
     }
 }
 
@@ -448,15 +1131,30 @@ impl RenderableObject for InputNumber {
                 format!("{:.*}", decimals as usize, displayed_value)
             };
 
+            let Some(style) = text_style(ui, pool, font_attributes, background_colour) else {
+                return;
+            };
+
+            // Non-proportional fonts are drawn from bitmaps, with leading zeros filling the whole
+            // characters that fit in the field.
+            let font_height = match font_attributes.font_size {
+                FontSize::NonProportional(size) => {
+                    let font = VtFont::new(font_attributes.font_type, size);
+                    if self.options.display_leading_zeros {
+                        let width = font.chars_fitting(rect.width());
+                        number_string = format!("{:0>width$}", number_string);
+                    }
+                    font.paint(ui, rect, &[number_string], self.justification, &style);
+                    return;
+                }
+                FontSize::Proportional(height) => height as f32,
+            };
+            let font_id = egui::FontId::new(font_height, egui::FontFamily::Proportional);
+
             // If the "display_leading_zeros" option is set, try to pad the text on the left with zeros
             // so that it fills (or exceeds) the available field width.
             if self.options.display_leading_zeros {
                 let fonts = ui.fonts(|f| f.clone());
-                let font_height = match font_attributes.font_size {
-                    FontSize::NonProportional(size) => size.height() as f32,
-                    FontSize::Proportional(h) => h as f32,
-                };
-                let font_id = egui::FontId::new(font_height, egui::FontFamily::Proportional);
                 let mut zero_padded = number_string.clone();
                 let max_loop = 1000; // safety to avoid an infinite loop
                 for _ in 0..max_loop {
@@ -474,26 +1172,8 @@ impl RenderableObject for InputNumber {
                 }
             }
 
-            // Get the font colour.
-            let font_colour = pool.color_by_index(font_attributes.font_colour).convert();
-
-            // Choose the font family and height according to the font size:
-            let (font_family, font_height) = match font_attributes.font_size {
-                FontSize::NonProportional(npsize) => {
-                    (egui::FontFamily::Monospace, npsize.height() as f32)
-                }
-                FontSize::Proportional(h) => (egui::FontFamily::Proportional, h as f32),
-            };
-            let font_id = egui::FontId::new(font_height, font_family);
-
             // Lay out the text.
-            let fonts = ui.fonts(|f| f.clone());
-            let galley = fonts.layout(
-                number_string.clone(),
-                font_id.clone(),
-                font_colour,
-                f32::INFINITY,
-            );
+            let galley = layout_styled_text(ui, number_string, font_id, &style, f32::INFINITY);
             let text_size = galley.size();
 
             // Compute the text’s paint position according to the horizontal and vertical justification.
@@ -536,18 +1216,18 @@ impl RenderableObject for InputNumber {
             }
 
             // Draw the number string.
-            ui.painter().galley(paint_pos, galley, font_colour);
-
-            // If the InputNumber object is not enabled (according to its InputNumberOptions),
-            // overlay a semi‐transparent gray rectangle.
-            if !self.options2.enabled {
-                ui.painter().rect_filled(
-                    rect,
-                    0.0,
-                    egui::Color32::from_rgba_premultiplied(128, 128, 128, 100),
-                );
-            }
+            paint_styled_text(ui, paint_pos, galley, &style);
         });
+
+        // If the InputNumber object is not enabled (according to its InputNumberOptions),
+        // overlay a semi‐transparent gray rectangle.
+        if !self.options2.enabled {
+            ui.painter().rect_filled(
+                rect,
+                0.0,
+                egui::Color32::from_rgba_premultiplied(128, 128, 128, 100),
+            );
+        }
     }
 }
 
@@ -559,15 +1239,63 @@ impl RenderableObject for InputList {
             egui::Vec2::new(self.width() as f32, self.height() as f32),
         );
 
-        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
-            ui.colored_label(Color32::RED, "InputList not implemented");
-        });
+        let index = resolve_number_value(pool, self.variable_reference, self.value as u32);
+        render_list_item(ui, pool, rect, &self.list_items, index);
+    }
+}
+
+impl RenderableObject for SoftKeyMask {
+    fn render(&self, ui: &mut egui::Ui, pool: &ObjectPool, _: Point<i16>) {
+        let rect = ui.max_rect();
+        ui.painter().rect_filled(
+            rect,
+            0.0,
+            pool.color_by_index(self.background_colour).convert(),
+        );
+
+        if self.objects.is_empty() {
+            return;
+        }
+
+        // The placement of the soft keys is up to the VT, so we stack the keys along the longest
+        // side of the available area.
+        let vertical = rect.height() >= rect.width();
+        let key_size = if vertical {
+            egui::vec2(rect.width(), rect.height() / self.objects.len() as f32)
+        } else {
+            egui::vec2(rect.width() / self.objects.len() as f32, rect.height())
+        };
+
+        for (index, key_id) in self.objects.iter().enumerate() {
+            let offset = if vertical {
+                egui::vec2(0.0, key_size.y * index as f32)
+            } else {
+                egui::vec2(key_size.x * index as f32, 0.0)
+            };
+            let key_rect = egui::Rect::from_min_size(rect.min + offset, key_size);
+
+            ui.scope_builder(UiBuilder::new().max_rect(key_rect), |ui| {
+                match pool.object_by_id(*key_id) {
+                    Some(key) => key.render(ui, pool, Point::default()),
+                    None => {
+                        ui.colored_label(Color32::RED, format!("Missing key: {:?}", key_id));
+                    }
+                }
+            });
+        }
     }
 }
 
 impl RenderableObject for Key {
     fn render(&self, ui: &mut egui::Ui, pool: &ObjectPool, position: Point<i16>) {
-        let rect = create_relative_rect(ui, position, egui::Vec2::new(100.0, 100.0));
+        // The size of a key is determined by the soft key designator it is placed in
+        let rect = create_relative_rect(ui, position, ui.max_rect().size());
+
+        ui.painter().rect_filled(
+            rect,
+            0.0,
+            pool.color_by_index(self.background_colour).convert(),
+        );
 
         ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
             render_object_refs(ui, pool, &self.object_refs);
@@ -611,20 +1339,8 @@ impl RenderableObject for OutputString {
                 return;
             }
         };
-        let background_colour = pool.color_by_index(self.background_colour).convert();
-
-        let transparent = self.options.transparent;
-        let auto_wrap = self.options.auto_wrap;
-
-        // TODO: check if VT version is 4 or later, if so implement wrap_on_hyphen
-        // let wrap_on_hyphen = self.options.wrap_on_hyphen;
-        // Note: wrap_on_hyphen behavior is complex. For simplicity here, we rely on normal word-wrapping
-        // from egui and do not implement special hyphenation logic. A more thorough implementation
-        // would detect hyphens and possibly treat them as break opportunities.
 
-        // According to the specification, we need to handle control characters (CR, LF) as line breaks.
-        // We'll normalize all line endings to '\n'.
-        let mut text_value = if let Some(variable_reference_id) = self.variable_reference.into() {
+        let value = if let Some(variable_reference_id) = self.variable_reference.into() {
             match pool.object_by_id(variable_reference_id) {
                 Some(Object::StringVariable(s)) => s.value.clone(),
                 _ => self.value.clone(),
@@ -632,132 +1348,17 @@ impl RenderableObject for OutputString {
         } else {
             self.value.clone()
         };
-        text_value = text_value
-            .replace("\r\n", "\n")
-            .replace("\n\r", "\n")
-            .replace('\r', "\n")
-            .replace('\x0a', "\n");
-
-        // Apply space trimming rules based on horizontal justification:
-        // - Left justification: no trimming of leading spaces (for the first line), trailing spaces remain as is.
-        // - Middle justification: remove leading and trailing spaces on each line.
-        // - Right justification: remove trailing spaces on each line.
-        let mut lines: Vec<&str> = text_value.split('\n').collect();
-        for (line_number, line) in lines.iter_mut().enumerate() {
-            match self.justification.horizontal {
-                HorizontalAlignment::Left => {
-                    // Per ISO rules, if auto-wrapping is enabled, leading spaces on wrapped lines might be removed.
-                    if auto_wrap && line_number > 0 {
-                        // Remove leading spaces
-                        *line = line.trim_start();
-                    }
-                }
-                HorizontalAlignment::Middle => {
-                    // Remove both leading and trailing spaces
-                    *line = line.trim();
-                }
-                HorizontalAlignment::Right => {
-                    // Remove trailing spaces only
-                    *line = line.trim_end();
-                }
-                HorizontalAlignment::Reserved => {
-                    ui.colored_label(
-                        Color32::RED,
-                        "Configuration incorrect: horizontal alignment is set to Reserved",
-                    );
-                    return;
-                }
-            }
-        }
-
-        let processed_text = lines.join("\n");
-
-        let font_colour = pool.color_by_index(font_attributes.font_colour).convert();
-        let fonts = ui.fonts(|fonts| fonts.clone());
-        let font_height;
-        let font_family;
-        match font_attributes.font_size {
-            FontSize::NonProportional(size) => {
-                font_family = egui::FontFamily::Monospace;
-
-                // We need to calculate the font height based on the width of a letter in the monospace font.
-                let font_size = fonts
-                    .layout_no_wrap(
-                        "a".into(),
-                        FontId::new(size.height() as f32, egui::FontFamily::Monospace),
-                        font_colour,
-                    )
-                    .size();
-
-                font_height = size.height() as f32 * (font_size.x / size.width() as f32);
-            }
-            FontSize::Proportional(height) => {
-                font_height = height as f32;
-                font_family = egui::FontFamily::Proportional;
-            }
-        }
 
-        let wrap_width = if auto_wrap {
-            self.width() as f32
-        } else {
-            f32::INFINITY
+        let field = StringField {
+            value,
+            font_attributes,
+            background_colour: self.background_colour,
+            transparent: self.options.transparent,
+            auto_wrap: self.options.auto_wrap,
+            wrap_on_hyphen: self.options.wrap_on_hyphen,
+            justification: self.justification,
         };
-
-        let galley = fonts.layout(
-            processed_text,
-            FontId::new(font_height, font_family.clone()),
-            font_colour,
-            wrap_width,
-        );
-
-        let text_size = galley.size();
-
-        let mut paint_pos = rect.min;
-
-        match self.justification.horizontal {
-            HorizontalAlignment::Left => {
-                paint_pos.x = rect.min.x;
-            }
-            HorizontalAlignment::Middle => {
-                paint_pos.x = rect.center().x - (text_size.x * 0.5);
-            }
-            HorizontalAlignment::Right => {
-                paint_pos.x = rect.max.x - text_size.x;
-            }
-            HorizontalAlignment::Reserved => {
-                ui.colored_label(
-                    Color32::RED,
-                    "Configuration incorrect: horizontal alignment is set to Reserved",
-                );
-                return;
-            }
-        };
-
-        match self.justification.vertical {
-            VerticalAlignment::Top => {
-                paint_pos.y = rect.min.y;
-            }
-            VerticalAlignment::Middle => {
-                paint_pos.y = rect.center().y - (text_size.y * 0.5);
-            }
-            VerticalAlignment::Bottom => {
-                paint_pos.y = rect.max.y - text_size.y;
-            }
-            VerticalAlignment::Reserved => {
-                ui.colored_label(
-                    Color32::RED,
-                    "Configuration incorrect: vertical alignment is set to Reserved",
-                );
-                return;
-            }
-        };
-
-        if !transparent {
-            let painter = ui.painter();
-            painter.rect_filled(rect, 0.0, background_colour);
-        }
-
-        ui.painter().galley(paint_pos, galley, font_colour);
+        render_string(ui, pool, rect, field);
     }
 }
 
@@ -787,7 +1388,6 @@ impl RenderableObject for OutputNumber {
 
             // 2. Convert the pool color indices to `egui::Color32`
             let background_colour = pool.color_by_index(self.background_colour).convert();
-            let font_colour = pool.color_by_index(font_attributes.font_colour).convert();
 
             // 3. Determine if we need to fill the background or remain transparent
             if !self.options.transparent {
@@ -839,25 +1439,31 @@ impl RenderableObject for OutputNumber {
                 format!("{:.*}", decimals as usize, displayed_value)
             };
 
-            // 9. The standard states that we must always display at least one digit
-            //    before the decimal point (i.e., "0.xxxx" if the absolute value < 1)
-            //    Normal Rust formatting already ensures e.g. "0.12" for 0.12,
-            //    so we usually don't need a special patch here. But we keep the note.
-            //
-            // 10. If display_leading_zeros is set, we *attempt* to fill the entire width
-            //     with zeros to the left before applying alignment. (ISO 11783 says
-            //     "fill left to width of field with zeros, then apply justification.")
-            //     Below is a best-effort approach: we measure the text in a loop,
-            //     and keep prepending '0' until it meets or exceeds the available width.
-            //     We also place a reasonable safety limit to avoid infinite loops.
-            //
+            // Resolve the colours and decorations from the font style, flashing text may be hidden
+            let Some(style) = text_style(ui, pool, font_attributes, background_colour) else {
+                return;
+            };
+
+            // Non-proportional fonts are drawn from bitmaps, with leading zeros filling the whole
+            // characters that fit in the field.
+            let font_height = match font_attributes.font_size {
+                FontSize::NonProportional(size) => {
+                    let font = VtFont::new(font_attributes.font_type, size);
+                    if self.options.display_leading_zeros {
+                        let width = font.chars_fitting(rect.width());
+                        number_string = format!("{:0>width$}", number_string);
+                    }
+                    font.paint(ui, rect, &[number_string], self.justification, &style);
+                    return;
+                }
+                FontSize::Proportional(height) => height as f32,
+            };
+            let font_id = egui::FontId::new(font_height, egui::FontFamily::Proportional);
+
+            // If display_leading_zeros is set, fill the width of the field with zeros to the left
+            // before applying the justification, keep prepending '0' until the text is wide enough
             if self.options.display_leading_zeros {
                 let fonts = ui.fonts(|f| f.clone());
-                let font_height = match font_attributes.font_size {
-                    FontSize::NonProportional(s) => s.height() as f32,
-                    FontSize::Proportional(h) => h as f32,
-                };
-                let font_id = egui::FontId::new(font_height, egui::FontFamily::Proportional);
                 let mut zero_padded = number_string.clone();
                 let max_loop = 1000; // safety net to avoid infinite loops
                 for _ in 0..max_loop {
@@ -865,7 +1471,7 @@ impl RenderableObject for OutputNumber {
                     let galley = fonts.layout(
                         zero_padded.as_str().to_owned(),
                         font_id.clone(),
-                        font_colour,
+                        style.colour,
                         f32::INFINITY, // no wrap
                     );
                     if galley.size().x >= rect.width() {
@@ -878,27 +1484,16 @@ impl RenderableObject for OutputNumber {
                 }
             }
 
-            // 11. We have the final text we want to display in `number_string`.
-            //     Next, figure out the font size and alignment. This is similar
-            //     to the `OutputString` example.
-            let fonts = ui.fonts(|fonts| fonts.clone());
-            let (font_family, font_height) = match font_attributes.font_size {
-                FontSize::NonProportional(npsize) => {
-                    // For simplicity, treat it as monospace
-                    (egui::FontFamily::Monospace, npsize.height() as f32)
-                }
-                FontSize::Proportional(h) => (egui::FontFamily::Proportional, h as f32),
-            };
-            let font_id = egui::FontId::new(font_height, font_family);
-            let galley = fonts.layout(
-                number_string.clone(),
-                font_id.clone(),
-                font_colour,
+            let galley = layout_styled_text(
+                ui,
+                number_string,
+                font_id,
+                &style,
                 f32::INFINITY, // no wrapping
             );
             let text_size = galley.size();
 
-            // 12. Determine text anchor point based on the justification bits
+            // Determine text anchor point based on the justification bits
             let mut paint_pos = rect.min;
             match self.justification.horizontal {
                 HorizontalAlignment::Left => {
@@ -937,8 +1532,8 @@ impl RenderableObject for OutputNumber {
                 }
             }
 
-            // 13. Finally, paint the text
-            ui.painter().galley(paint_pos, galley, font_colour);
+            // Finally, paint the text
+            paint_styled_text(ui, paint_pos, galley, &style);
         });
     }
 }
@@ -951,9 +1546,8 @@ impl RenderableObject for OutputList {
             egui::Vec2::new(self.width() as f32, self.height() as f32),
         );
 
-        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
-            ui.colored_label(Color32::RED, "OutputList not implemented");
-        });
+        let index = resolve_number_value(pool, self.variable_reference, self.value as u32);
+        render_list_item(ui, pool, rect, &self.list_items, index);
     }
 }
 
@@ -985,43 +1579,19 @@ impl RenderableObject for OutputLine {
                 return;
             }
 
-            let colour = pool.color_by_index(line_attributes.line_colour).convert();
-            let stroke_width = line_attributes.line_width as f32;
-            let stroke = egui::Stroke::new(stroke_width, colour);
-            // TODO: implement line art
-
-            let (start, end) = match self.line_direction {
-                LineDirection::TopLeftToBottomRight => {
-                    let start = rect.min;
-                    let mut end = rect.max - egui::vec2(stroke_width, stroke_width);
-
-                    // Clamp end to start
-                    if end.x < start.x {
-                        end.x = start.x;
-                    }
-                    if end.y < start.y {
-                        end.y = start.y;
-                    }
-
-                    (start, end)
-                }
-                LineDirection::BottomLeftToTopRight => {
-                    let mut start = egui::pos2(rect.left(), rect.bottom() + stroke_width);
-                    let mut end = egui::pos2(rect.right() - stroke_width, rect.top());
-
-                    // Clamping start and end
-                    if end.x < start.x {
-                        end.x = start.x;
-                    }
-                    if start.y < end.y {
-                        start.y = end.y;
-                    }
+            // The line runs through the centre of the paintbrush, which stays inside the bounds
+            let inset = line_attributes.line_width as f32 / 2.0;
+            let inner = rect.shrink2(egui::vec2(
+                inset.min(rect.width() / 2.0),
+                inset.min(rect.height() / 2.0),
+            ));
 
-                    (start, end)
-                }
+            let points = match self.line_direction {
+                LineDirection::TopLeftToBottomRight => vec![inner.left_top(), inner.right_bottom()],
+                LineDirection::BottomLeftToTopRight => vec![inner.left_bottom(), inner.right_top()],
             };
 
-            ui.painter().line_segment([start, end], stroke);
+            stroke_path(ui.painter(), pool, line_attributes, points, false);
         });
     }
 }
@@ -1054,25 +1624,47 @@ impl RenderableObject for OutputRectangle {
                     return;
                 }
             };
-            ui.painter().rect_filled(
-                rect,
-                0.0,
-                pool.color_by_index(fill_attributes.fill_colour).convert(),
-            );
-            // TODO: implement fill type for infill
-            // TODO: implement fill pattern for infill
+            if let Some(fill) = shape_fill(ui, pool, fill_attributes, line_attributes, rect.min) {
+                fill_rects(ui.painter(), vec![rect], &fill);
+            }
         }
 
-        ui.painter().rect_stroke(
-            rect,
-            0.0,
-            egui::Stroke::new(
-                line_attributes.line_width,
-                pool.color_by_index(line_attributes.line_colour).convert(),
-            ),
-            egui::StrokeKind::Inside,
-        );
-        // TODO: implement line art for border
+        // The border is drawn inside the bounds of the rectangle
+        let inset = line_attributes.line_width as f32 / 2.0;
+        let border_rect = rect.shrink2(egui::vec2(
+            inset.min(rect.width() / 2.0),
+            inset.min(rect.height() / 2.0),
+        ));
+        let corners = [
+            border_rect.left_top(),
+            border_rect.right_top(),
+            border_rect.right_bottom(),
+            border_rect.left_bottom(),
+        ];
+
+        // Line suppression bits 0 to 3 hide the top, right, bottom and left side respectively
+        let suppressed = |side: usize| self.line_suppression & (1 << side) != 0;
+        match (0..4).find(|side| suppressed(*side)) {
+            None => stroke_path(ui.painter(), pool, line_attributes, corners.to_vec(), true),
+            Some(first) => {
+                // Draw each run of visible sides as one line, so the line art continues around
+                // the corners
+                let mut run = Vec::new();
+                for side in (1..=4).map(|i| (first + i) % 4) {
+                    if suppressed(side) {
+                        if !run.is_empty() {
+                            stroke_path(ui.painter(), pool, line_attributes, run, false);
+                        }
+                        run = Vec::new();
+                    } else {
+                        if run.is_empty() {
+                            run.push(corners[side]);
+                        }
+                        run.push(corners[(side + 1) % 4]);
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -1084,9 +1676,64 @@ impl RenderableObject for OutputEllipse {
             egui::Vec2::new(self.width() as f32, self.height() as f32),
         );
 
-        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
-            ui.colored_label(Color32::RED, "OutputEllipse not implemented");
-        });
+        let line_attributes = match pool.object_by_id(self.line_attributes) {
+            Some(Object::LineAttributes(l)) => l,
+            _ => {
+                ui.colored_label(
+                    Color32::RED,
+                    format!("Missing line attributes: {:?}", self.line_attributes),
+                );
+                return;
+            }
+        };
+        let fill_attributes = match self.fill_attributes.into() {
+            Some(fill) => match pool.object_by_id(fill) {
+                Some(Object::FillAttributes(f)) => Some(f),
+                _ => {
+                    ui.colored_label(Color32::RED, format!("Missing fill attributes: {:?}", fill));
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        // The border of the ellipse is drawn inside the bounds of the object. Use the full size of
+        // the object, the painter clips whatever doesn't fit.
+        let ellipse_rect = egui::Rect::from_min_size(
+            rect.min,
+            egui::vec2(self.width() as f32, self.height() as f32),
+        )
+        .shrink(line_attributes.line_width as f32 / 2.0);
+
+        // Angles are in units of 2 degrees, measured counter-clockwise from the positive x-axis.
+        // Equal start and end angles result in a full ellipse.
+        let full_ellipse = self.ellipse_type == 0 || self.start_angle == self.end_angle;
+        let mut points = if full_ellipse {
+            let mut points = ellipse_arc_points(ellipse_rect, 0.0, TAU);
+            points.pop(); // The last point is the same as the first one
+            points
+        } else {
+            let start = (self.start_angle as f32 * 2.0).to_radians();
+            let end = (self.end_angle as f32 * 2.0).to_radians();
+            ellipse_arc_points(ellipse_rect, start, end)
+        };
+
+        // Type 0: closed ellipse, 1: open ellipse, 2: closed ellipse segment (chord),
+        // 3: closed ellipse section (pie)
+        if self.ellipse_type == 3 && !full_ellipse {
+            points.push(ellipse_rect.center());
+        }
+        let closed = self.ellipse_type != 1 || full_ellipse;
+
+        let painter = ui.painter_at(rect);
+        if self.ellipse_type != 1 {
+            if let Some(fill) = fill_attributes
+                .and_then(|fill| shape_fill(ui, pool, fill, line_attributes, rect.min))
+            {
+                fill_polygon(&painter, &points, &fill);
+            }
+        }
+        stroke_path(&painter, pool, line_attributes, points, closed);
     }
 }
 
@@ -1098,9 +1745,47 @@ impl RenderableObject for OutputPolygon {
             egui::Vec2::new(self.width() as f32, self.height() as f32),
         );
 
-        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
-            ui.colored_label(Color32::RED, "OutputPolygon not implemented");
-        });
+        let line_attributes = match pool.object_by_id(self.line_attributes) {
+            Some(Object::LineAttributes(l)) => l,
+            _ => {
+                ui.colored_label(
+                    Color32::RED,
+                    format!("Missing line attributes: {:?}", self.line_attributes),
+                );
+                return;
+            }
+        };
+        let fill_attributes = match self.fill_attributes.into() {
+            Some(fill) => match pool.object_by_id(fill) {
+                Some(Object::FillAttributes(f)) => Some(f),
+                _ => {
+                    ui.colored_label(Color32::RED, format!("Missing fill attributes: {:?}", fill));
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        // The points are relative to the top left corner of the polygon object
+        let points: Vec<egui::Pos2> = self
+            .points
+            .iter()
+            .map(|p| rect.min + egui::vec2(p.x as f32, p.y as f32))
+            .collect();
+
+        // Type 0: convex, 1: non-convex, 2: complex, 3: open. Only the open polygon is not closed
+        // and can't be filled. The span based fill handles all closed types with the even-odd rule.
+        let closed = self.polygon_type != 3;
+
+        let painter = ui.painter_at(rect);
+        if closed {
+            if let Some(fill) = fill_attributes
+                .and_then(|fill| shape_fill(ui, pool, fill, line_attributes, rect.min))
+            {
+                fill_polygon(&painter, &points, &fill);
+            }
+        }
+        stroke_path(&painter, pool, line_attributes, points, closed);
     }
 }
 
@@ -1112,9 +1797,63 @@ impl RenderableObject for OutputMeter {
             egui::Vec2::new(self.width() as f32, self.height() as f32),
         );
 
-        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
-            ui.colored_label(Color32::RED, "OutputMeter not implemented");
-        });
+        // The meter is always a square, use the full size and let the painter clip what doesn't fit
+        let meter_rect =
+            egui::Rect::from_min_size(rect.min, egui::Vec2::splat(self.width() as f32));
+        let painter = ui.painter_at(rect);
+
+        let value = resolve_number_value(pool, self.variable_reference, self.value as u32);
+        let fraction = value_fraction(value, self.min_value as u32, self.max_value as u32);
+
+        let (start, sweep) = deflection_arc(
+            self.start_angle,
+            self.end_angle,
+            self.options.deflection_direction,
+        );
+        let radius = meter_rect.width() / 2.0;
+        let centre = meter_rect.center();
+
+        if self.options.draw_border {
+            painter.circle_stroke(
+                centre,
+                radius - 0.5,
+                egui::Stroke::new(1.0, pool.color_by_index(self.border_colour).convert()),
+            );
+        }
+
+        // Keep some distance between the border and the arc and ticks
+        let arc_rect = meter_rect.shrink(radius * 0.1 + 1.0);
+        let arc_and_tick_colour = pool.color_by_index(self.arc_and_tick_colour).convert();
+
+        if self.options.draw_arc {
+            painter.add(egui::Shape::line(
+                ellipse_sweep_points(arc_rect, start, sweep),
+                egui::Stroke::new(1.0, arc_and_tick_colour),
+            ));
+        }
+
+        if self.options.draw_ticks && self.nr_of_ticks > 0 {
+            // Ticks point inwards from the arc, the first and last tick are at the ends of the arc
+            let tick_rect = arc_rect.shrink(radius * 0.15);
+            for i in 0..self.nr_of_ticks {
+                let angle = start + sweep * tick_fraction(i, self.nr_of_ticks);
+                painter.line_segment(
+                    [
+                        ellipse_point(arc_rect, angle),
+                        ellipse_point(tick_rect, angle),
+                    ],
+                    egui::Stroke::new(1.0, arc_and_tick_colour),
+                );
+            }
+        }
+
+        let needle_colour = pool.color_by_index(self.needle_colour).convert();
+        let needle_rect = arc_rect.shrink(radius * 0.05);
+        painter.line_segment(
+            [centre, ellipse_point(needle_rect, start + sweep * fraction)],
+            egui::Stroke::new((radius / 25.0).max(1.0), needle_colour),
+        );
+        painter.circle_filled(centre, (radius / 15.0).max(1.0), needle_colour);
     }
 }
 
@@ -1126,9 +1865,102 @@ impl RenderableObject for OutputLinearBarGraph {
             egui::Vec2::new(self.width() as f32, self.height() as f32),
         );
 
-        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
-            ui.colored_label(Color32::RED, "OutputLinearBarGraph not implemented");
-        });
+        let painter = ui.painter_at(rect);
+        let colour = pool.color_by_index(self.colour).convert();
+
+        let min = self.min_value as u32;
+        let max = self.max_value as u32;
+        let value = resolve_number_value(pool, self.variable_reference, self.value as u32);
+        let target_value = resolve_number_value(
+            pool,
+            self.target_value_variable_reference,
+            self.target_value as u32,
+        );
+
+        // Get the line across the bar at the given fraction of the axis, taking the axis
+        // orientation and grow direction into account.
+        let horizontal = self.options.axis_orientation == AxisOrientation::Horizontal;
+        let grow_right_up = self.options.grow_direction == GrowDirection::GrowRightUp;
+        let bar_rect = egui::Rect::from_min_size(
+            rect.min,
+            egui::vec2(self.width() as f32, self.height() as f32),
+        );
+        let line_at = |fraction: f32| -> [egui::Pos2; 2] {
+            match (horizontal, grow_right_up) {
+                (true, true) => {
+                    let x = bar_rect.left() + bar_rect.width() * fraction;
+                    [
+                        egui::pos2(x, bar_rect.top()),
+                        egui::pos2(x, bar_rect.bottom()),
+                    ]
+                }
+                (true, false) => {
+                    let x = bar_rect.right() - bar_rect.width() * fraction;
+                    [
+                        egui::pos2(x, bar_rect.top()),
+                        egui::pos2(x, bar_rect.bottom()),
+                    ]
+                }
+                (false, true) => {
+                    let y = bar_rect.bottom() - bar_rect.height() * fraction;
+                    [
+                        egui::pos2(bar_rect.left(), y),
+                        egui::pos2(bar_rect.right(), y),
+                    ]
+                }
+                (false, false) => {
+                    let y = bar_rect.top() + bar_rect.height() * fraction;
+                    [
+                        egui::pos2(bar_rect.left(), y),
+                        egui::pos2(bar_rect.right(), y),
+                    ]
+                }
+            }
+        };
+
+        let value_line = line_at(value_fraction(value, min, max));
+        match self.options.bar_graph_type {
+            BarGraphType::Filled => {
+                let origin = line_at(0.0);
+                painter.rect_filled(
+                    egui::Rect::from_two_pos(origin[0], value_line[1]),
+                    0.0,
+                    colour,
+                );
+            }
+            BarGraphType::NotFilled => {
+                painter.line_segment(value_line, egui::Stroke::new(1.0, colour));
+            }
+        }
+
+        if self.options.draw_ticks {
+            // Ticks are drawn from the left or bottom edge, a quarter of the way into the bar
+            for i in 0..self.nr_of_ticks {
+                let [a, b] = line_at(tick_fraction(i, self.nr_of_ticks));
+                let tick = if horizontal {
+                    [b, b + (a - b) / 4.0]
+                } else {
+                    [a, a + (b - a) / 4.0]
+                };
+                painter.line_segment(tick, egui::Stroke::new(1.0, colour));
+            }
+        }
+
+        if self.options.draw_target_line {
+            painter.line_segment(
+                line_at(value_fraction(target_value, min, max)),
+                egui::Stroke::new(1.0, pool.color_by_index(self.target_line_colour).convert()),
+            );
+        }
+
+        if self.options.draw_border {
+            painter.rect_stroke(
+                bar_rect,
+                0.0,
+                egui::Stroke::new(1.0, colour),
+                egui::StrokeKind::Inside,
+            );
+        }
     }
 }
 
@@ -1140,53 +1972,118 @@ impl RenderableObject for OutputArchedBarGraph {
             egui::Vec2::new(self.width() as f32, self.height() as f32),
         );
 
-        ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
-            ui.colored_label(Color32::RED, "OutputArchedBarGraph not implemented");
-        });
-    }
-}
+        let painter = ui.painter_at(rect);
+        let colour = pool.color_by_index(self.colour).convert();
 
-impl RenderableObject for PictureGraphic {
-    fn render(&self, ui: &mut egui::Ui, pool: &ObjectPool, position: Point<i16>) {
-        let rect = create_relative_rect(
-            ui,
-            position,
-            egui::Vec2::new(self.width() as f32, self.height() as f32),
+        let min = self.min_value as u32;
+        let max = self.max_value as u32;
+        let value = resolve_number_value(pool, self.variable_reference, self.value as u32);
+        let target_value = resolve_number_value(
+            pool,
+            self.target_value_variable_reference,
+            self.target_value as u32,
         );
 
-        let mut hasher = DefaultHasher::new();
-        Object::PictureGraphic(self.clone())
-            .write()
-            .hash(&mut hasher);
-        let hash = hasher.finish();
-
-        let changed: bool = ui.data_mut(|data| {
-            let old_hash: Option<u64> =
-                data.get_temp(format!("picturegraphic_{}_image", self.id.value()).into());
-            if old_hash.is_none() || old_hash.unwrap() != hash {
-                data.insert_temp(
-                    format!("picturegraphic_{}_image", self.id.value()).into(),
-                    hash,
-                );
-                true
+        // The bar is the band between the ellipse that fits the object and the ellipse that is
+        // `bar_graph_width` smaller on each side.
+        let outer_rect = egui::Rect::from_min_size(
+            rect.min,
+            egui::vec2(self.width() as f32, self.height() as f32),
+        );
+        let bar_width = (self.bar_graph_width as f32)
+            .min(outer_rect.width() / 2.0)
+            .min(outer_rect.height() / 2.0);
+        let inner_rect = outer_rect.shrink(bar_width);
+
+        let (start, sweep) = deflection_arc(
+            self.start_angle,
+            self.end_angle,
+            self.options.deflection_direction,
+        );
+        let band_points = |sweep: f32| -> Vec<egui::Pos2> {
+            let mut points = ellipse_sweep_points(outer_rect, start, sweep);
+            points.extend(
+                ellipse_sweep_points(inner_rect, start, sweep)
+                    .into_iter()
+                    .rev(),
+            );
+            points
+        };
+        let line_at = |fraction: f32| -> [egui::Pos2; 2] {
+            let angle = start + sweep * fraction;
+            [
+                ellipse_point(outer_rect, angle),
+                ellipse_point(inner_rect, angle),
+            ]
+        };
+
+        let fraction = value_fraction(value, min, max);
+        match self.options.bar_graph_type {
+            BarGraphType::Filled => {
+                if fraction > 0.0 {
+                    fill_polygon(
+                        &painter,
+                        &band_points(sweep * fraction),
+                        &ShapeFill::Colour(colour),
+                    );
+                }
+            }
+            BarGraphType::NotFilled => {
+                painter.line_segment(line_at(fraction), egui::Stroke::new(1.0, colour));
+            }
+        }
+
+        if self.options.draw_target_line {
+            painter.line_segment(
+                line_at(value_fraction(target_value, min, max)),
+                egui::Stroke::new(1.0, pool.color_by_index(self.target_line_colour).convert()),
+            );
+        }
+
+        if self.options.draw_border {
+            let stroke = egui::Stroke::new(1.0, colour);
+            if sweep.abs() >= TAU {
+                // A full circle has no ends, so draw both ellipses separately
+                painter.add(egui::Shape::closed_line(
+                    ellipse_sweep_points(outer_rect, start, sweep),
+                    stroke,
+                ));
+                painter.add(egui::Shape::closed_line(
+                    ellipse_sweep_points(inner_rect, start, sweep),
+                    stroke,
+                ));
             } else {
-                false
+                painter.add(egui::Shape::closed_line(band_points(sweep), stroke));
             }
-        });
+        }
+    }
+}
 
-        let texture_id: Option<TextureId>;
-        if changed {
+/// Get the texture of a picture graphic. The decoded image is cached in the egui temp data and
+/// only decoded again when the pool changes, see `set_pool_revision`. The texture repeats, so it
+/// can also be tiled as a fill pattern.
+fn picture_graphic_texture(
+    ui: &egui::Ui,
+    pool: &ObjectPool,
+    picture: &PictureGraphic,
+) -> Option<TextureId> {
+    let revision = pool_revision(ui.ctx());
+    let texture_key: egui::Id = format!("picturegraphic_{}_texture", picture.id.value()).into();
+    let cached = ui.data(|data| data.get_temp::<(u64, TextureHandle)>(texture_key));
+    match cached {
+        Some((cached_revision, texture)) if cached_revision == revision => Some(texture.id()),
+        _ => {
             let mut x = 0;
             let mut y = 0;
 
             let mut image = ColorImage::filled(
-                [self.actual_width.into(), self.actual_height.into()],
+                [picture.actual_width.into(), picture.actual_height.into()],
                 Color32::TRANSPARENT,
             );
 
-            for raw in self.data_as_raw_encoded() {
+            for raw in picture.data_as_raw_encoded() {
                 let mut colors: Vec<Color32> = vec![];
-                match self.format {
+                match picture.format {
                     PictureGraphicFormat::Monochrome => {
                         for bit in 0..8 {
                             colors.push(pool.color_by_index((raw >> (7 - bit)) & 0x01).convert());
@@ -1204,18 +2101,18 @@ impl RenderableObject for PictureGraphic {
                 }
 
                 for color in colors {
-                    let idx = y as usize * self.actual_width as usize + x as usize;
+                    let idx = y as usize * picture.actual_width as usize + x as usize;
                     if idx >= image.pixels.len() {
                         break;
                     }
-                    if !(self.options.transparent
-                        && color == pool.color_by_index(self.transparency_colour).convert())
+                    if !(picture.options.transparent
+                        && color == pool.color_by_index(picture.transparency_colour).convert())
                     {
                         image.pixels[idx] = color;
                     }
 
                     x += 1;
-                    if x >= self.actual_width {
+                    if x >= picture.actual_width {
                         x = 0;
                         y += 1;
                         // If we go onto the next row, then we discard the rest of the bits
@@ -1225,26 +2122,26 @@ impl RenderableObject for PictureGraphic {
             }
 
             let new_texture = ui.ctx().load_texture(
-                format!("picturegraphic_{}_texture", self.id.value()).as_str(),
+                format!("picturegraphic_{}_texture", picture.id.value()).as_str(),
                 image,
-                Default::default(),
+                TextureOptions::NEAREST_REPEAT,
             );
-            texture_id = Some(new_texture.id());
-            ui.data_mut(|data| {
-                println!("Saving texture - {:?}", self.id.value());
-                data.insert_temp(
-                    format!("picturegraphic_{}_texture", self.id.value()).into(),
-                    new_texture,
-                );
-            });
-        } else {
-            texture_id = ui.data(|data| {
-                data.get_temp::<TextureHandle>(
-                    format!("picturegraphic_{}_texture", self.id.value()).into(),
-                )
-                .map(|t| t.id())
-            });
+            let texture_id = new_texture.id();
+            ui.data_mut(|data| data.insert_temp(texture_key, (revision, new_texture)));
+            Some(texture_id)
         }
+    }
+}
+
+impl RenderableObject for PictureGraphic {
+    fn render(&self, ui: &mut egui::Ui, pool: &ObjectPool, position: Point<i16>) {
+        let rect = create_relative_rect(
+            ui,
+            position,
+            egui::Vec2::new(self.width() as f32, self.height() as f32),
+        );
+
+        let texture_id = picture_graphic_texture(ui, pool, self);
 
         ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
             if let Some(texture_id) = texture_id {
@@ -1276,3 +2173,92 @@ impl RenderableObject for AuxiliaryControlDesignatorType2 {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(x: f32, y: f32) -> egui::Pos2 {
+        egui::pos2(x, y)
+    }
+
+    #[test]
+    fn solid_line_art_is_one_dash() {
+        let line = [pos(0.0, 0.0), pos(10.0, 0.0)];
+        assert_eq!(line_art_dashes(&line, 0xFFFF), vec![line.to_vec()]);
+
+        // The dash continues around corners
+        let corner = [pos(0.0, 0.0), pos(4.0, 0.0), pos(4.0, 4.0)];
+        assert_eq!(line_art_dashes(&corner, 0xFFFF), vec![corner.to_vec()]);
+    }
+
+    #[test]
+    fn empty_line_art_has_no_dashes() {
+        let line = [pos(0.0, 0.0), pos(10.0, 0.0), pos(10.0, 10.0)];
+        assert!(line_art_dashes(&line, 0x0000).is_empty());
+    }
+
+    #[test]
+    fn line_art_pattern_repeats() {
+        let line = [pos(0.0, 0.0), pos(32.0, 0.0)];
+        assert_eq!(
+            line_art_dashes(&line, 0xFF00),
+            vec![
+                vec![pos(0.0, 0.0), pos(8.0, 0.0)],
+                vec![pos(16.0, 0.0), pos(24.0, 0.0)],
+            ]
+        );
+
+        // Each bit covers one pixel, starting with the most significant bit
+        let line = [pos(0.0, 0.0), pos(4.0, 0.0)];
+        assert_eq!(
+            line_art_dashes(&line, 0xAAAA),
+            vec![
+                vec![pos(0.0, 0.0), pos(1.0, 0.0)],
+                vec![pos(2.0, 0.0), pos(3.0, 0.0)],
+            ]
+        );
+    }
+
+    fn span(min_x: f32, max_x: f32, y: f32) -> egui::Rect {
+        egui::Rect::from_min_max(pos(min_x, y), pos(max_x, y + 1.0))
+    }
+
+    #[test]
+    fn concave_polygon_spans() {
+        // A U shape, open at the bottom
+        let points = [
+            pos(0.0, 0.0),
+            pos(6.0, 0.0),
+            pos(6.0, 4.0),
+            pos(4.0, 4.0),
+            pos(4.0, 2.0),
+            pos(2.0, 2.0),
+            pos(2.0, 4.0),
+            pos(0.0, 4.0),
+        ];
+        assert_eq!(
+            polygon_spans(&points),
+            vec![
+                span(0.0, 6.0, 0.0),
+                span(0.0, 6.0, 1.0),
+                span(0.0, 2.0, 2.0),
+                span(4.0, 6.0, 2.0),
+                span(0.0, 2.0, 3.0),
+                span(4.0, 6.0, 3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn degenerate_polygon_spans() {
+        assert!(polygon_spans(&[]).is_empty());
+        assert!(polygon_spans(&[pos(0.0, 0.0), pos(5.0, 5.0)]).is_empty());
+        // Points on a horizontal line cover no rows
+        assert!(polygon_spans(&[pos(0.0, 0.0), pos(5.0, 0.0), pos(10.0, 0.0)]).is_empty());
+        // Points on a vertical line cover no area
+        let spans = polygon_spans(&[pos(2.0, 0.0), pos(2.0, 3.0), pos(2.0, 6.0)]);
+        assert_eq!(spans.len(), 6);
+        assert!(spans.iter().all(|span| span.width() == 0.0));
+    }
+}
@@ -0,0 +1,123 @@
+//! Copyright 2024 - The Open-Agriculture Developers
+//! SPDX-License-Identifier: GPL-3.0-or-later
+//! Authors: Daan Steenbergen
+
+use ag_iso_stack::object_pool::object::Object;
+
+/// How the size of an object can be changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resizable {
+    /// The width and height can be changed independently
+    Free,
+    /// Only the width can be changed, the height follows from it
+    WidthOnly,
+}
+
+/// Get how the size of an object can be changed, or None if its size is fixed or determined by
+/// the VT, like for masks and keys
+pub fn resizable(object: &Object) -> Option<Resizable> {
+    match object {
+        Object::Container(_)
+        | Object::Button(_)
+        | Object::InputString(_)
+        | Object::InputNumber(_)
+        | Object::InputList(_)
+        | Object::OutputString(_)
+        | Object::OutputNumber(_)
+        | Object::OutputList(_)
+        | Object::OutputLine(_)
+        | Object::OutputRectangle(_)
+        | Object::OutputEllipse(_)
+        | Object::OutputPolygon(_)
+        | Object::OutputLinearBarGraph(_)
+        | Object::OutputArchedBarGraph(_)
+        | Object::Animation(_)
+        | Object::ScaledGraphic(_) => Some(Resizable::Free),
+        // Square objects, and pictures that keep the aspect ratio of their actual size
+        Object::InputBoolean(_) | Object::OutputMeter(_) | Object::PictureGraphic(_) => {
+            Some(Resizable::WidthOnly)
+        }
+        _ => None,
+    }
+}
+
+/// Set the width and height attributes of an object. The height is ignored for objects that can
+/// only change their width, and the points of a polygon are scaled to the new size.
+/// The size is at least 1, as a polygon scaled to a size of 0 would lose the shape of its points.
+/// Scaling rounds the points, so scale from the original object once instead of step by step.
+pub fn set_object_size(object: &mut Object, width: u16, height: u16) {
+    let (width, height) = (width.max(1), height.max(1));
+    match object {
+        Object::Container(o) => (o.width, o.height) = (width, height),
+        Object::Button(o) => (o.width, o.height) = (width, height),
+        Object::InputString(o) => (o.width, o.height) = (width, height),
+        Object::InputNumber(o) => (o.width, o.height) = (width, height),
+        Object::InputList(o) => (o.width, o.height) = (width, height),
+        Object::OutputString(o) => (o.width, o.height) = (width, height),
+        Object::OutputNumber(o) => (o.width, o.height) = (width, height),
+        Object::OutputList(o) => (o.width, o.height) = (width, height),
+        Object::OutputLine(o) => (o.width, o.height) = (width, height),
+        Object::OutputRectangle(o) => (o.width, o.height) = (width, height),
+        Object::OutputEllipse(o) => (o.width, o.height) = (width, height),
+        Object::OutputPolygon(o) => {
+            let scale = |value: u16, old: u16, new: u16| {
+                if old == 0 {
+                    value
+                } else {
+                    let (value, old, new) = (value as u32, old as u32, new as u32);
+                    ((value * new + old / 2) / old).min(u16::MAX as u32) as u16
+                }
+            };
+            for point in o.points.iter_mut() {
+                point.x = scale(point.x, o.width, width);
+                point.y = scale(point.y, o.height, height);
+            }
+            (o.width, o.height) = (width, height);
+        }
+        Object::OutputLinearBarGraph(o) => (o.width, o.height) = (width, height),
+        Object::OutputArchedBarGraph(o) => (o.width, o.height) = (width, height),
+        Object::Animation(o) => (o.width, o.height) = (width, height),
+        Object::ScaledGraphic(o) => (o.width, o.height) = (width, height),
+        Object::InputBoolean(o) => o.width = width,
+        Object::OutputMeter(o) => o.width = width,
+        Object::PictureGraphic(o) => o.width = width,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ag_iso_stack::object_pool::object_attributes::Point;
+    use ag_iso_stack::object_pool::ObjectType;
+
+    fn polygon(width: u16, height: u16, points: &[(u16, u16)]) -> Object {
+        let mut object = crate::default_object(ObjectType::OutputPolygon);
+        if let Object::OutputPolygon(o) = &mut object {
+            (o.width, o.height) = (width, height);
+            o.points = points.iter().map(|&(x, y)| Point { x, y }).collect();
+        }
+        object
+    }
+
+    fn points(object: &Object) -> Vec<(u16, u16)> {
+        match object {
+            Object::OutputPolygon(o) => o.points.iter().map(|p| (p.x, p.y)).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn polygon_points_are_rounded() {
+        let mut object = polygon(3, 3, &[(0, 0), (1, 1), (2, 2), (3, 3)]);
+        set_object_size(&mut object, 5, 4);
+        assert_eq!(points(&object), vec![(0, 0), (2, 1), (3, 3), (5, 4)]);
+    }
+
+    #[test]
+    fn size_is_at_least_one() {
+        let mut object = polygon(10, 10, &[(0, 0), (5, 10), (10, 0)]);
+        set_object_size(&mut object, 0, 0);
+        assert_eq!(points(&object), vec![(0, 0), (1, 1), (1, 0)]);
+    }
+}
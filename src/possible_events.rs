@@ -2,7 +2,50 @@
 //! SPDX-License-Identifier: GPL-3.0-or-later
 //! Authors: Daan Steenbergen
 
-use ag_iso_stack::object_pool::{object::*, object_attributes::Event};
+use ag_iso_stack::object_pool::{object::*, object_attributes::Event, ObjectType};
+
+pub fn get_possible_events(obj_type: ObjectType) -> Vec<Event> {
+    match obj_type {
+        ObjectType::WorkingSet => WorkingSet::get_possible_events(),
+        ObjectType::DataMask => DataMask::get_possible_events(),
+        ObjectType::AlarmMask => AlarmMask::get_possible_events(),
+        ObjectType::Container => Container::get_possible_events(),
+        ObjectType::SoftKeyMask => SoftKeyMask::get_possible_events(),
+        ObjectType::Key => Key::get_possible_events(),
+        ObjectType::Button => Button::get_possible_events(),
+        ObjectType::InputBoolean => InputBoolean::get_possible_events(),
+        ObjectType::InputString => InputString::get_possible_events(),
+        ObjectType::InputNumber => InputNumber::get_possible_events(),
+        ObjectType::InputList => InputList::get_possible_events(),
+        ObjectType::OutputString => OutputString::get_possible_events(),
+        ObjectType::OutputNumber => OutputNumber::get_possible_events(),
+        ObjectType::OutputList => OutputList::get_possible_events(),
+        ObjectType::OutputLine => OutputLine::get_possible_events(),
+        ObjectType::OutputRectangle => OutputRectangle::get_possible_events(),
+        ObjectType::OutputEllipse => OutputEllipse::get_possible_events(),
+        ObjectType::OutputPolygon => OutputPolygon::get_possible_events(),
+        ObjectType::OutputMeter => OutputMeter::get_possible_events(),
+        ObjectType::OutputLinearBarGraph => OutputLinearBarGraph::get_possible_events(),
+        ObjectType::OutputArchedBarGraph => OutputArchedBarGraph::get_possible_events(),
+        ObjectType::PictureGraphic => PictureGraphic::get_possible_events(),
+        ObjectType::NumberVariable => NumberVariable::get_possible_events(),
+        ObjectType::StringVariable => StringVariable::get_possible_events(),
+        ObjectType::FontAttributes => FontAttributes::get_possible_events(),
+        ObjectType::LineAttributes => LineAttributes::get_possible_events(),
+        ObjectType::FillAttributes => FillAttributes::get_possible_events(),
+        ObjectType::InputAttributes => InputAttributes::get_possible_events(),
+        ObjectType::ObjectPointer => ObjectPointer::get_possible_events(),
+        ObjectType::GraphicsContext => GraphicsContext::get_possible_events(),
+        ObjectType::KeyGroup => KeyGroup::get_possible_events(),
+        ObjectType::ExternalObjectDefinition => ExternalObjectDefinition::get_possible_events(),
+        ObjectType::WindowMask => WindowMask::get_possible_events(),
+        ObjectType::ExternalReferenceName => ExternalReferenceName::get_possible_events(),
+        ObjectType::ExternalObjectPointer => ExternalObjectPointer::get_possible_events(),
+        ObjectType::Animation => Animation::get_possible_events(),
+        ObjectType::ScaledGraphic => ScaledGraphic::get_possible_events(),
+        _ => vec![],
+    }
+}
 
 pub trait PossibleEvents {
     fn get_possible_events() -> Vec<Event>;
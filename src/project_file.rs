@@ -45,6 +45,64 @@ pub struct ProjectSettings {
     
     /// Last selected object ID
     pub last_selected: Option<u16>,
+
+    /// Number of physical soft keys for preview
+    #[serde(default = "default_soft_key_count")]
+    pub soft_key_count: u8,
+
+    /// Placement of the soft keys next to the mask for preview
+    #[serde(default)]
+    pub soft_key_placement: SoftKeyPlacement,
+
+    /// Version number of the VT the object pool is designed for
+    #[serde(default = "default_vt_version")]
+    pub vt_version: u8,
+
+    /// Grid and snapping settings for positioning objects in the preview
+    #[serde(default)]
+    pub grid: GridSettings,
+}
+
+/// Placement of the soft key designators relative to the data mask
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SoftKeyPlacement {
+    Left,
+    #[default]
+    Right,
+    Bottom,
+}
+
+/// Grid shown over the mask preview, and what dragged objects snap to
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct GridSettings {
+    /// Distance between the grid lines in pixels
+    pub size: u16,
+    /// Draw the grid lines over the preview
+    pub show: bool,
+    /// Snap the edges of dragged objects to the grid lines
+    pub snap_to_grid: bool,
+    /// Snap the edges and centres of dragged objects to those of the parent and sibling objects
+    pub snap_to_objects: bool,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        GridSettings {
+            size: 10,
+            show: false,
+            snap_to_grid: false,
+            snap_to_objects: true,
+        }
+    }
+}
+
+fn default_soft_key_count() -> u8 {
+    6
+}
+
+fn default_vt_version() -> u8 {
+    3
 }
 
 impl ProjectFile {
@@ -52,8 +110,7 @@ impl ProjectFile {
     pub fn new(
         pool: &ObjectPool,
         object_info: &HashMap<ObjectId, ObjectInfo>,
-        mask_size: u16,
-        selected: Option<ObjectId>,
+        settings: ProjectSettings,
     ) -> Self {
         // Convert ObjectInfo map to ObjectMetadata map
         let mut object_metadata = HashMap::new();
@@ -69,10 +126,7 @@ impl ProjectFile {
             version: PROJECT_FILE_VERSION,
             object_pool_data: pool.as_iop(),
             object_metadata,
-            settings: ProjectSettings {
-                mask_size,
-                last_selected: selected.map(|id| id.value()),
-            },
+            settings,
         }
     }
     
@@ -122,6 +176,10 @@ impl Default for ProjectSettings {
         ProjectSettings {
             mask_size: 500,
             last_selected: None,
+            soft_key_count: default_soft_key_count(),
+            soft_key_placement: SoftKeyPlacement::default(),
+            vt_version: default_vt_version(),
+            grid: GridSettings::default(),
         }
     }
 }
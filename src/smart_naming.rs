@@ -109,6 +109,41 @@ pub fn generate_smart_default_name(
     }
 }
 
+/// Generates a name for a copy of an object, e.g. "Main Screen copy" or "Main Screen copy 2".
+/// Copying a copy doesn't add another suffix, the copy number is increased instead.
+pub fn generate_copy_name(name: &str, existing_names: &HashMap<String, usize>) -> String {
+    let base_name = strip_copy_suffix(name);
+
+    let candidate = format!("{} copy", base_name);
+    if existing_names.get(&candidate).copied().unwrap_or(0) == 0 {
+        return candidate;
+    }
+
+    let mut counter = 2;
+    loop {
+        let candidate = format!("{} copy {}", base_name, counter);
+        if existing_names.get(&candidate).copied().unwrap_or(0) == 0 {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+/// Removes a " copy" or " copy N" suffix from a name
+fn strip_copy_suffix(name: &str) -> &str {
+    if let Some(base_name) = name.strip_suffix(" copy") {
+        return base_name;
+    }
+    match name.rsplit_once(" copy ") {
+        Some((base_name, number))
+            if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            base_name
+        }
+        _ => name,
+    }
+}
+
 /// Generates contextual names for specific object types based on their properties
 pub fn generate_contextual_name(object: &Object, pool: &ObjectPool) -> Option<String> {
     match object {
@@ -0,0 +1,374 @@
+//! Copyright 2024 - The Open-Agriculture Developers
+//! SPDX-License-Identifier: GPL-3.0-or-later
+//! Authors: Daan Steenbergen
+
+use std::collections::{HashMap, VecDeque};
+
+use ag_iso_stack::object_pool::{object::Object, ObjectId, ObjectPool};
+
+use crate::bulk_editing::changed_attributes;
+use crate::ObjectInfo;
+
+/// An object before and after a change, None if it didn't exist
+#[derive(Clone)]
+struct ObjectChange {
+    id: ObjectId,
+    before: Option<Object>,
+    after: Option<Object>,
+}
+
+/// The info of an object, like its name, before and after a change
+#[derive(Clone)]
+struct InfoChange {
+    id: ObjectId,
+    before: Option<ObjectInfo>,
+    after: Option<ObjectInfo>,
+}
+
+/// The changes made to the project in a single step, only the changed objects are stored
+#[derive(Clone, Default)]
+pub struct HistoryEntry {
+    /// Human readable description of the action, shown in the history
+    label: String,
+    objects: Vec<ObjectChange>,
+    infos: Vec<InfoChange>,
+
+    /// The order of the objects in the pool before and after the change, if it changed
+    order: Option<(Vec<ObjectId>, Vec<ObjectId>)>,
+}
+
+impl HistoryEntry {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+
+    /// Describe the changes, for actions that weren't labeled explicitly.
+    /// `name` returns the name of a changed object.
+    pub fn describe(&self, name: impl Fn(&Object) -> String) -> String {
+        match self.objects.as_slice() {
+            [] => {}
+            [change] => {
+                return match (&change.before, &change.after) {
+                    (None, Some(after)) => format!("Add {}", name(after)),
+                    (Some(before), None) => format!("Delete {}", name(before)),
+                    (Some(before), Some(after)) => match changed_attributes(before, after) {
+                        attributes if attributes.is_empty() => format!("Change {}", name(after)),
+                        attributes => {
+                            format!("Change {} of {}", attributes.join(", "), name(after))
+                        }
+                    },
+                    (None, None) => String::new(),
+                };
+            }
+            changes => {
+                let count = changes.len();
+                return if changes.iter().all(|change| change.before.is_none()) {
+                    format!("Add {} objects", count)
+                } else if changes.iter().all(|change| change.after.is_none()) {
+                    format!("Delete {} objects", count)
+                } else {
+                    format!("Change {} objects", count)
+                };
+            }
+        }
+
+        match self.infos.as_slice() {
+            [] => {}
+            [change] => {
+                let info_name = |info: &Option<ObjectInfo>| {
+                    info.as_ref()
+                        .and_then(|info| info.name.clone())
+                        .unwrap_or_else(|| format!("object {}", change.id.value()))
+                };
+                return format!(
+                    "Rename {} to {}",
+                    info_name(&change.before),
+                    info_name(&change.after)
+                );
+            }
+            changes => return format!("Rename {} objects", changes.len()),
+        }
+
+        "Reorder objects".to_string()
+    }
+
+    /// Check if the entry doesn't contain any changes
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.infos.is_empty() && self.order.is_none()
+    }
+
+    /// Check if objects were added or removed
+    pub fn adds_or_removes_objects(&self) -> bool {
+        self.objects
+            .iter()
+            .any(|change| change.before.is_none() || change.after.is_none())
+    }
+
+    /// Record the change of an object, if it actually changed
+    pub fn record_object(&mut self, id: ObjectId, before: Option<Object>, after: Option<Object>) {
+        if before != after {
+            self.objects.push(ObjectChange { id, before, after });
+        }
+    }
+
+    /// Record the change of an object info, if its name changed or it was added or removed
+    pub fn record_info(
+        &mut self,
+        id: ObjectId,
+        before: Option<ObjectInfo>,
+        after: Option<ObjectInfo>,
+    ) {
+        let changed = match (&before, &after) {
+            (Some(before), Some(after)) => before != after || before.name != after.name,
+            (None, None) => false,
+            _ => true,
+        };
+        if changed {
+            self.infos.push(InfoChange { id, before, after });
+        }
+    }
+
+    /// Record the order of the objects in the pool, if it changed
+    pub fn record_order(&mut self, before: Vec<ObjectId>, after: Vec<ObjectId>) {
+        if before != after {
+            self.order = Some((before, after));
+        }
+    }
+
+    /// Apply the object changes to a pool, forward to redo or backward to undo them
+    pub fn apply_objects(&self, pool: &mut ObjectPool, forward: bool) {
+        for change in &self.objects {
+            let target = if forward {
+                &change.after
+            } else {
+                &change.before
+            };
+            match (pool.object_mut_by_id(change.id), target) {
+                (Some(object), Some(target)) => *object = target.clone(),
+                (Some(_), None) => {
+                    pool.remove(change.id);
+                }
+                (None, Some(target)) => pool.add(target.clone()),
+                (None, None) => {}
+            }
+        }
+
+        if let Some((before, after)) = &self.order {
+            let order = if forward { after } else { before };
+            let positions: HashMap<ObjectId, usize> = order
+                .iter()
+                .enumerate()
+                .map(|(position, id)| (*id, position))
+                .collect();
+            pool.objects_mut()
+                .sort_by_key(|object| positions.get(&object.id()).copied());
+        }
+    }
+
+    /// Apply the object info changes, forward to redo or backward to undo them
+    pub fn apply_infos(&self, object_info: &mut HashMap<ObjectId, ObjectInfo>, forward: bool) {
+        for change in &self.infos {
+            let target = if forward {
+                &change.after
+            } else {
+                &change.before
+            };
+            match target {
+                Some(info) => {
+                    object_info.insert(change.id, info.clone());
+                }
+                None => {
+                    object_info.remove(&change.id);
+                }
+            }
+        }
+    }
+}
+
+/// The undo and redo history of a project. The depth of the undo history is unlimited, unless a
+/// maximum depth is set.
+#[derive(Clone, Default)]
+pub struct UndoHistory {
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    max_depth: Option<usize>,
+}
+
+impl UndoHistory {
+    /// Add a new step to the history, this clears the redo history
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.redo.clear();
+        self.undo.push_back(entry);
+        self.trim();
+    }
+
+    /// Set the maximum number of steps that can be undone, None for unlimited
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+        self.trim();
+    }
+
+    /// Remove the oldest steps that exceed the maximum depth
+    fn trim(&mut self) {
+        if let Some(max_depth) = self.max_depth {
+            while self.undo.len() > max_depth {
+                self.undo.pop_front();
+            }
+        }
+    }
+
+    /// Move the last step to the redo history and return it, so it can be undone
+    pub fn undo(&mut self) -> Option<&HistoryEntry> {
+        let entry = self.undo.pop_back()?;
+        self.redo.push(entry);
+        self.redo.last()
+    }
+
+    /// Move the last undone step back to the undo history and return it, so it can be redone
+    pub fn redo(&mut self) -> Option<&HistoryEntry> {
+        let entry = self.redo.pop()?;
+        self.undo.push_back(entry);
+        self.undo.back()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The number of actions that can be undone
+    pub fn position(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// The actions that can be undone, oldest first
+    pub fn undo_entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.undo.iter()
+    }
+
+    /// The actions that can be redone, the next one to redo first
+    pub fn redo_entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.redo.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ag_iso_stack::object_pool::ObjectType;
+
+    fn container(id: u16, width: u16) -> Object {
+        let mut object = crate::default_object(ObjectType::Container);
+        *object.mut_id() = ObjectId::new(id).unwrap();
+        if let Object::Container(o) = &mut object {
+            o.width = width;
+        }
+        object
+    }
+
+    fn labeled(label: &str) -> HistoryEntry {
+        let mut entry = HistoryEntry::default();
+        entry.set_label(label.to_string());
+        entry
+    }
+
+    fn labels<'a>(entries: impl Iterator<Item = &'a HistoryEntry>) -> Vec<&'a str> {
+        entries.map(|entry| entry.label()).collect()
+    }
+
+    #[test]
+    fn entry_round_trip() {
+        let id = |id| ObjectId::new(id).unwrap();
+        let mut pool = ObjectPool::new();
+        pool.add(container(1, 10));
+        pool.add(container(2, 20));
+        let before = pool.objects().to_vec();
+
+        let mut entry = HistoryEntry::default();
+        entry.record_object(id(1), Some(container(1, 10)), Some(container(1, 15)));
+        entry.record_object(id(2), Some(container(2, 20)), None);
+        entry.record_object(id(3), None, Some(container(3, 30)));
+        // Unchanged objects are not recorded
+        entry.record_object(id(4), Some(container(4, 40)), Some(container(4, 40)));
+        entry.record_order(vec![id(1), id(2)], vec![id(3), id(1)]);
+        assert!(entry.adds_or_removes_objects());
+        assert_eq!(entry.describe(|_| String::new()), "Change 3 objects");
+
+        entry.apply_objects(&mut pool, true);
+        assert_eq!(pool.objects(), &[container(3, 30), container(1, 15)]);
+        entry.apply_objects(&mut pool, false);
+        assert_eq!(pool.objects().to_vec(), before);
+
+        let mut object_info = HashMap::new();
+        let mut info = ObjectInfo::new(&container(1, 10));
+        info.name = Some("Before".to_string());
+        object_info.insert(id(1), info.clone());
+        let mut renamed = info.clone();
+        renamed.name = Some("After".to_string());
+
+        let mut entry = HistoryEntry::default();
+        entry.record_info(id(1), Some(info.clone()), Some(renamed));
+        entry.record_info(id(2), Some(info.clone()), Some(info.clone()));
+        assert_eq!(entry.describe(|_| String::new()), "Rename Before to After");
+
+        entry.apply_infos(&mut object_info, true);
+        assert_eq!(object_info[&id(1)].name.as_deref(), Some("After"));
+        entry.apply_infos(&mut object_info, false);
+        assert_eq!(object_info[&id(1)].name.as_deref(), Some("Before"));
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut history = UndoHistory::default();
+        assert!(!history.can_undo() && !history.can_redo());
+        history.push(labeled("one"));
+        history.push(labeled("two"));
+
+        assert_eq!(history.undo().map(HistoryEntry::label), Some("two"));
+        assert_eq!(history.undo().map(HistoryEntry::label), Some("one"));
+        assert!(history.undo().is_none());
+        assert_eq!(labels(history.redo_entries()), vec!["one", "two"]);
+
+        assert_eq!(history.redo().map(HistoryEntry::label), Some("one"));
+        assert_eq!(history.position(), 1);
+        assert!(history.can_undo() && history.can_redo());
+
+        // A new step clears the redo history
+        history.push(labeled("three"));
+        assert!(!history.can_redo());
+        assert_eq!(labels(history.undo_entries()), vec!["one", "three"]);
+    }
+
+    #[test]
+    fn max_depth_drops_oldest_steps() {
+        let mut history = UndoHistory::default();
+        for label in ["one", "two", "three", "four"] {
+            history.push(labeled(label));
+        }
+        history.set_max_depth(Some(3));
+        assert_eq!(labels(history.undo_entries()), vec!["two", "three", "four"]);
+
+        history.push(labeled("five"));
+        assert_eq!(
+            labels(history.undo_entries()),
+            vec!["three", "four", "five"]
+        );
+
+        // Undone steps don't count towards the depth
+        history.undo();
+        history.set_max_depth(Some(2));
+        assert_eq!(labels(history.undo_entries()), vec!["three", "four"]);
+        assert_eq!(labels(history.redo_entries()), vec!["five"]);
+
+        history.set_max_depth(None);
+        history.push(labeled("six"));
+        assert_eq!(history.position(), 3);
+    }
+}
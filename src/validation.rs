@@ -0,0 +1,532 @@
+//! Copyright 2024 - The Open-Agriculture Developers
+//! SPDX-License-Identifier: GPL-3.0-or-later
+//! Authors: Daan Steenbergen
+
+use std::collections::{HashMap, HashSet};
+
+use ag_iso_stack::object_pool::{
+    object::Object,
+    object_attributes::{FontSize, FontType, MacroRef},
+    vt_version::VtVersion,
+    ObjectId, ObjectPool, ObjectType,
+};
+
+use crate::allowed_object_relationships::get_allowed_child_refs;
+use crate::object_references::{max_macro_id, referenced_ids, visit_references, Reference};
+use crate::possible_events::get_possible_events;
+
+/// Angles are given in units of 2 degrees, so 180 is a full circle
+const MAX_ANGLE: u8 = 180;
+
+/// The maximum number of decimals of a number object
+const MAX_DECIMALS: u8 = 7;
+
+/// The smallest height of a proportional font in pixels
+const MIN_PROPORTIONAL_FONT_HEIGHT: u8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The VT will most likely reject the object pool
+    Error,
+    /// The object pool is accepted, but probably doesn't behave as intended
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// The offending object, or None if the issue concerns the pool as a whole
+    pub object_id: Option<ObjectId>,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn error(object_id: Option<ObjectId>, message: String) -> Self {
+        ValidationIssue {
+            severity: Severity::Error,
+            object_id,
+            message,
+        }
+    }
+
+    fn warning(object_id: Option<ObjectId>, message: String) -> Self {
+        ValidationIssue {
+            severity: Severity::Warning,
+            object_id,
+            message,
+        }
+    }
+}
+
+/// Check the object pool against the rules of ISO 11783-6 for the given VT version.
+/// The issues are sorted by severity, errors first.
+pub fn validate_pool(pool: &ObjectPool, version: VtVersion) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    validate_working_set(pool, &mut issues);
+    for object in pool.objects() {
+        validate_references(pool, object, &mut issues);
+        validate_macro_id(object, version, &mut issues);
+        validate_children(pool, object, version, &mut issues);
+        validate_events(object, &mut issues);
+        validate_attributes(object, &mut issues);
+        validate_key_codes(pool, object, &mut issues);
+    }
+
+    issues.sort_by_key(|issue| issue.severity);
+    issues
+}
+
+fn validate_working_set(pool: &ObjectPool, issues: &mut Vec<ValidationIssue>) {
+    let working_sets = pool.objects_by_type(ObjectType::WorkingSet);
+    if working_sets.len() > 1 {
+        for working_set in &working_sets[1..] {
+            issues.push(ValidationIssue::error(
+                Some(working_set.id()),
+                "Only one working set is allowed in an object pool".to_string(),
+            ));
+        }
+    }
+
+    let Some(working_set) = pool.working_set_object() else {
+        issues.push(ValidationIssue::error(
+            None,
+            "The object pool has no working set".to_string(),
+        ));
+        return;
+    };
+    match pool.object_by_id(working_set.active_mask) {
+        Some(Object::DataMask(_)) | Some(Object::AlarmMask(_)) => {}
+        Some(object) => issues.push(ValidationIssue::error(
+            Some(working_set.id),
+            format!(
+                "The active mask {} is a {:?}, not a data mask or alarm mask",
+                u16::from(object.id()),
+                object.object_type()
+            ),
+        )),
+        None => issues.push(ValidationIssue::error(
+            Some(working_set.id),
+            format!(
+                "The active mask {} does not exist",
+                u16::from(working_set.active_mask)
+            ),
+        )),
+    }
+}
+
+/// Get the macro references of an object
+fn macro_refs(object: &Object) -> Vec<MacroRef> {
+    let mut macro_refs = Vec::new();
+    visit_references(object, |reference| {
+        if let Reference::MacroRefs(list) = reference {
+            macro_refs.extend_from_slice(list);
+        }
+    });
+    macro_refs
+}
+
+/// Get the child objects of an object, which are listed in its object references or object lists
+fn child_ids(object: &Object) -> Vec<ObjectId> {
+    let mut children = Vec::new();
+    visit_references(object, |reference| match reference {
+        Reference::Ids(list) => children.extend(list.iter().copied()),
+        Reference::NullableIds(list) => children.extend(list.iter().filter_map(|id| id.0)),
+        Reference::ObjectRefs(list) => children.extend(list.iter().map(|object_ref| object_ref.id)),
+        Reference::Id(_)
+        | Reference::NullableId(_)
+        | Reference::MacroRefs(_)
+        | Reference::MacroCommands(_) => {}
+    });
+    children
+}
+
+fn validate_references(pool: &ObjectPool, object: &Object, issues: &mut Vec<ValidationIssue>) {
+    // Missing macros are reported once, instead of as missing objects as well
+    let mut reported = HashSet::new();
+    for macro_ref in macro_refs(object) {
+        let Ok(macro_id) = ObjectId::new(macro_ref.macro_id as u16) else {
+            continue;
+        };
+        match pool.object_by_id(macro_id) {
+            Some(macro_object) if macro_object.object_type() != ObjectType::Macro => {
+                issues.push(ValidationIssue::error(
+                    Some(object.id()),
+                    format!(
+                        "The {:?} event references {:?} {} instead of a macro",
+                        macro_ref.event_id,
+                        macro_object.object_type(),
+                        macro_ref.macro_id
+                    ),
+                ));
+            }
+            Some(_) => {}
+            None if reported.insert(macro_id) => issues.push(ValidationIssue::error(
+                Some(object.id()),
+                format!(
+                    "References macro {} which does not exist",
+                    macro_ref.macro_id
+                ),
+            )),
+            None => {}
+        }
+    }
+
+    for id in referenced_ids(object) {
+        if pool.object_by_id(id).is_some() || !reported.insert(id) {
+            continue;
+        }
+        issues.push(ValidationIssue::error(
+            Some(object.id()),
+            format!("References object {} which does not exist", u16::from(id)),
+        ));
+    }
+}
+
+/// Up to VT version 4 macros are referenced by a single byte, so their ID can't be larger
+fn validate_macro_id(object: &Object, version: VtVersion, issues: &mut Vec<ValidationIssue>) {
+    let max_id = max_macro_id(version);
+    if object.object_type() == ObjectType::Macro && object.id().value() > max_id {
+        issues.push(ValidationIssue::error(
+            Some(object.id()),
+            format!(
+                "The macro ID {} is larger than the maximum of {} in this VT version",
+                object.id().value(),
+                max_id
+            ),
+        ));
+    }
+}
+
+fn validate_children(
+    pool: &ObjectPool,
+    object: &Object,
+    version: VtVersion,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let allowed = get_allowed_child_refs(object.object_type(), version);
+    if allowed.is_empty() {
+        return;
+    }
+    for child in child_ids(object)
+        .iter()
+        .filter_map(|id| pool.object_by_id(*id))
+    {
+        if !allowed.contains(&child.object_type()) {
+            issues.push(ValidationIssue::error(
+                Some(object.id()),
+                format!(
+                    "{:?} {} is not allowed as a child of a {:?} in this VT version",
+                    child.object_type(),
+                    u16::from(child.id()),
+                    object.object_type()
+                ),
+            ));
+        }
+    }
+}
+
+fn validate_events(object: &Object, issues: &mut Vec<ValidationIssue>) {
+    let possible_events = get_possible_events(object.object_type());
+    for macro_ref in macro_refs(object) {
+        if !possible_events.contains(&macro_ref.event_id) {
+            issues.push(ValidationIssue::error(
+                Some(object.id()),
+                format!(
+                    "The {:?} event is not available for a {:?}",
+                    macro_ref.event_id,
+                    object.object_type()
+                ),
+            ));
+        }
+    }
+}
+
+fn validate_range(
+    object: &Object,
+    min_value: u32,
+    max_value: u32,
+    value: u32,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    if min_value > max_value {
+        issues.push(ValidationIssue::error(
+            Some(object.id()),
+            format!(
+                "The minimum value {} is larger than the maximum value {}",
+                min_value, max_value
+            ),
+        ));
+    } else if !(min_value..=max_value).contains(&value) {
+        issues.push(ValidationIssue::warning(
+            Some(object.id()),
+            format!(
+                "The value {} is outside of the range {} to {}",
+                value, min_value, max_value
+            ),
+        ));
+    }
+}
+
+fn validate_angles(
+    object: &Object,
+    start_angle: u8,
+    end_angle: u8,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    for (name, angle) in [("start", start_angle), ("end", end_angle)] {
+        if angle > MAX_ANGLE {
+            issues.push(ValidationIssue::error(
+                Some(object.id()),
+                format!(
+                    "The {} angle {} is larger than the maximum of {}",
+                    name, angle, MAX_ANGLE
+                ),
+            ));
+        }
+    }
+}
+
+fn validate_decimals(object: &Object, nr_of_decimals: u8, issues: &mut Vec<ValidationIssue>) {
+    if nr_of_decimals > MAX_DECIMALS {
+        issues.push(ValidationIssue::error(
+            Some(object.id()),
+            format!(
+                "The number of decimals {} is larger than the maximum of {}",
+                nr_of_decimals, MAX_DECIMALS
+            ),
+        ));
+    }
+}
+
+fn validate_list_value(
+    object: &Object,
+    value: u8,
+    item_count: usize,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    // A value of 255 selects no item at all
+    if value != u8::MAX && value as usize >= item_count {
+        issues.push(ValidationIssue::warning(
+            Some(object.id()),
+            format!(
+                "The value {} doesn't select any of the {} list items",
+                value, item_count
+            ),
+        ));
+    }
+}
+
+fn validate_attributes(object: &Object, issues: &mut Vec<ValidationIssue>) {
+    match object {
+        Object::InputNumber(o) => {
+            validate_range(object, o.min_value, o.max_value, o.value, issues);
+            validate_decimals(object, o.nr_of_decimals, issues);
+        }
+        Object::OutputNumber(o) => validate_decimals(object, o.nr_of_decimals, issues),
+        Object::InputList(o) => validate_list_value(object, o.value, o.list_items.len(), issues),
+        Object::OutputList(o) => validate_list_value(object, o.value, o.list_items.len(), issues),
+        Object::OutputMeter(o) => {
+            let (min_value, max_value) = (o.min_value as u32, o.max_value as u32);
+            validate_range(object, min_value, max_value, o.value as u32, issues);
+            validate_angles(object, o.start_angle, o.end_angle, issues);
+        }
+        Object::OutputLinearBarGraph(o) => {
+            let (min_value, max_value) = (o.min_value as u32, o.max_value as u32);
+            validate_range(object, min_value, max_value, o.value as u32, issues);
+        }
+        Object::OutputArchedBarGraph(o) => {
+            let (min_value, max_value) = (o.min_value as u32, o.max_value as u32);
+            validate_range(object, min_value, max_value, o.value as u32, issues);
+            validate_angles(object, o.start_angle, o.end_angle, issues);
+        }
+        Object::OutputEllipse(o) => validate_angles(object, o.start_angle, o.end_angle, issues),
+        Object::OutputPolygon(o) if o.points.len() < 3 => {
+            issues.push(ValidationIssue::error(
+                Some(object.id()),
+                format!("A polygon needs at least 3 points, not {}", o.points.len()),
+            ));
+        }
+        Object::FontAttributes(o) => {
+            if let FontSize::Proportional(height) = o.font_size {
+                if height < MIN_PROPORTIONAL_FONT_HEIGHT {
+                    issues.push(ValidationIssue::error(
+                        Some(object.id()),
+                        format!(
+                            "The proportional font height {} is smaller than the minimum of {}",
+                            height, MIN_PROPORTIONAL_FONT_HEIGHT
+                        ),
+                    ));
+                }
+            }
+            if let FontType::Reserved(value) = o.font_type {
+                issues.push(ValidationIssue::error(
+                    Some(object.id()),
+                    format!("The font type {} is reserved", value),
+                ));
+            }
+        }
+        Object::FillAttributes(o) => match o.fill_type {
+            0..=2 => {}
+            3 if o.fill_pattern.0.is_none() => issues.push(ValidationIssue::error(
+                Some(object.id()),
+                "The fill type is a pattern, but no fill pattern is set".to_string(),
+            )),
+            3 => {}
+            fill_type => issues.push(ValidationIssue::error(
+                Some(object.id()),
+                format!("The fill type {} is not valid", fill_type),
+            )),
+        },
+        _ => {}
+    }
+}
+
+/// Keys and buttons are reported to the working set by key code, which should therefore be
+/// unique among the keys and buttons of the same parent
+fn validate_key_codes(pool: &ObjectPool, object: &Object, issues: &mut Vec<ValidationIssue>) {
+    let mut key_codes: HashMap<u8, &Object> = HashMap::new();
+    for child in child_ids(object)
+        .iter()
+        .filter_map(|id| pool.object_by_id(*id))
+    {
+        let key_code = match child {
+            Object::Key(o) => o.key_code,
+            Object::Button(o) => o.key_code,
+            _ => continue,
+        };
+        match key_codes.get(&key_code) {
+            Some(first) if first.id() != child.id() => {
+                issues.push(ValidationIssue::warning(
+                    Some(child.id()),
+                    format!(
+                        "Key code {} is also used by {:?} {} in {:?} {}",
+                        key_code,
+                        first.object_type(),
+                        u16::from(first.id()),
+                        object.object_type(),
+                        u16::from(object.id())
+                    ),
+                ));
+            }
+            Some(_) => {}
+            None => {
+                key_codes.insert(key_code, child);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ag_iso_stack::object_pool::{
+        object_attributes::{Event, MacroRef},
+        NullableObjectId, ObjectRef,
+    };
+
+    use super::*;
+    use crate::default_object;
+
+    fn object_with_id(object_type: ObjectType, id: u16) -> Object {
+        let mut object = default_object(object_type);
+        *object.mut_id() = ObjectId::new(id).unwrap();
+        object
+    }
+
+    /// Get the messages of the errors reported for an object
+    fn errors(issues: &[ValidationIssue], object_id: Option<u16>) -> Vec<&str> {
+        issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .filter(|issue| issue.object_id.map(|id| id.value()) == object_id)
+            .map(|issue| issue.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn missing_working_set() {
+        let issues = validate_pool(&ObjectPool::new(), VtVersion::Version3);
+        assert_eq!(
+            errors(&issues, None),
+            vec!["The object pool has no working set"]
+        );
+    }
+
+    #[test]
+    fn missing_reference() {
+        let mut container = object_with_id(ObjectType::Container, 10);
+        if let Object::Container(o) = &mut container {
+            o.object_refs.push(ObjectRef {
+                id: ObjectId::new(50).unwrap(),
+                offset: Default::default(),
+            });
+        }
+        let mut pool = ObjectPool::new();
+        pool.add(container);
+
+        let issues = validate_pool(&pool, VtVersion::Version3);
+        assert_eq!(
+            errors(&issues, Some(10)),
+            vec!["References object 50 which does not exist"]
+        );
+    }
+
+    #[test]
+    fn missing_macro() {
+        let mut button = object_with_id(ObjectType::Button, 10);
+        if let Object::Button(o) = &mut button {
+            o.macro_refs.push(MacroRef {
+                macro_id: 7,
+                event_id: Event::OnKeyPress,
+            });
+        }
+        let mut pool = ObjectPool::new();
+        pool.add(button);
+
+        let issues = validate_pool(&pool, VtVersion::Version3);
+        assert_eq!(
+            errors(&issues, Some(10)),
+            vec!["References macro 7 which does not exist"]
+        );
+    }
+
+    #[test]
+    fn macro_id_above_255() {
+        let mut pool = ObjectPool::new();
+        pool.add(object_with_id(ObjectType::Macro, 300));
+
+        let issues = validate_pool(&pool, VtVersion::Version4);
+        assert_eq!(
+            errors(&issues, Some(300)),
+            vec!["The macro ID 300 is larger than the maximum of 255 in this VT version"]
+        );
+        let issues = validate_pool(&pool, VtVersion::Version5);
+        assert!(errors(&issues, Some(300)).is_empty());
+    }
+
+    #[test]
+    fn bad_fill_type() {
+        let mut invalid = object_with_id(ObjectType::FillAttributes, 10);
+        let mut pattern_without_picture = object_with_id(ObjectType::FillAttributes, 11);
+        if let Object::FillAttributes(o) = &mut invalid {
+            o.fill_type = 4;
+        }
+        if let Object::FillAttributes(o) = &mut pattern_without_picture {
+            o.fill_type = 3;
+            o.fill_pattern = NullableObjectId::NULL;
+        }
+        let mut pool = ObjectPool::new();
+        pool.add(invalid);
+        pool.add(pattern_without_picture);
+
+        let issues = validate_pool(&pool, VtVersion::Version3);
+        assert_eq!(
+            errors(&issues, Some(10)),
+            vec!["The fill type 4 is not valid"]
+        );
+        assert_eq!(
+            errors(&issues, Some(11)),
+            vec!["The fill type is a pattern, but no fill pattern is set"]
+        );
+    }
+}
@@ -0,0 +1,531 @@
+//! Copyright 2024 - The Open-Agriculture Developers
+//! SPDX-License-Identifier: GPL-3.0-or-later
+//! Authors: Daan Steenbergen
+
+use ag_iso_stack::object_pool::object_attributes::{
+    Alignment, FontType, HorizontalAlignment, NonProportionalFontSize, VerticalAlignment,
+};
+use eframe::egui;
+use eframe::egui::{Color32, ColorImage, Mesh, Rect, TextureHandle, TextureOptions};
+
+/// The sizes of the bundled glyph bitmaps, every font size is an integer multiple of one of them
+const BITMAP_SIZES: [(usize, usize); 4] = [(6, 8), (8, 8), (8, 12), (12, 16)];
+
+/// Horizontal shift of italic text per pixel of height
+const ITALIC_SLANT: f32 = 0.25;
+
+/// The ISO 8859 code page used to interpret the bytes of a string for a font type
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum CodePage {
+    Latin1,
+    Latin9,
+    Latin2,
+    Latin4,
+    Cyrillic,
+    Greek,
+}
+
+impl CodePage {
+    fn from_font_type(font_type: FontType) -> Self {
+        match font_type {
+            FontType::Latin1 => CodePage::Latin1,
+            FontType::Latin9 => CodePage::Latin9,
+            FontType::Latin2 => CodePage::Latin2,
+            FontType::Latin4 => CodePage::Latin4,
+            FontType::Cyrillic => CodePage::Cyrillic,
+            FontType::Greek => CodePage::Greek,
+            // Terminals fall back to ISO 8859-1 for reserved and proprietary font types
+            _ => CodePage::Latin1,
+        }
+    }
+
+    fn upper_half(&self) -> Option<&'static [char; 96]> {
+        match self {
+            CodePage::Latin1 => None,
+            CodePage::Latin9 => Some(&LATIN_9),
+            CodePage::Latin2 => Some(&LATIN_2),
+            CodePage::Latin4 => Some(&LATIN_4),
+            CodePage::Cyrillic => Some(&CYRILLIC),
+            CodePage::Greek => Some(&GREEK),
+        }
+    }
+
+    /// The character shown for a byte, or `None` for control codes and unassigned bytes
+    fn decode(&self, byte: u8) -> Option<char> {
+        let c = match (byte, self.upper_half()) {
+            (0x00..=0x1F | 0x7F..=0x9F, _) => return None,
+            (0xA0..=0xFF, Some(table)) => table[byte as usize - 0xA0],
+            _ => byte as char,
+        };
+        (c != '\0').then_some(c)
+    }
+
+    /// The byte for a character of a string.
+    ///
+    /// Characters of the code page are mapped to their byte, other characters up to 0xFF are taken
+    /// as the raw byte value, anything else is shown as a question mark.
+    fn encode(&self, c: char) -> u8 {
+        if c.is_ascii() {
+            return c as u8;
+        }
+        if let Some(table) = self.upper_half() {
+            if let Some(index) = table.iter().position(|t| *t == c) {
+                return 0xA0 + index as u8;
+            }
+        }
+        u8::try_from(c as u32).unwrap_or(b'?')
+    }
+}
+
+/// How to paint text, resolved from the colours and the font style of the font attributes
+pub struct TextStyle {
+    pub colour: Color32,
+    /// Colour behind the characters, used for inverted text
+    pub background: Option<Color32>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub crossed_out: bool,
+}
+
+/// A non-proportional VT font, rendered from the bitmap glyphs per font type and size in
+/// `assets/vt_fonts`
+pub struct VtFont {
+    code_page: CodePage,
+    size: NonProportionalFontSize,
+}
+
+impl VtFont {
+    pub fn new(font_type: FontType, size: NonProportionalFontSize) -> Self {
+        VtFont {
+            code_page: CodePage::from_font_type(font_type),
+            size,
+        }
+    }
+
+    /// The size of a single character cell
+    pub fn char_size(&self) -> egui::Vec2 {
+        egui::vec2(self.size.width() as f32, self.size.height() as f32)
+    }
+
+    /// The number of whole characters that fit in the given width
+    pub fn chars_fitting(&self, width: f32) -> usize {
+        (width.max(0.0) / self.char_size().x).floor() as usize
+    }
+
+    /// Split the text into lines on line feeds and, if a wrap width is given, word wrap the lines
+    /// to the number of whole characters that fit in that width. Words longer than a line are
+    /// broken at the last character that fits, or after a hyphen if `wrap_on_hyphen` is set.
+    pub fn layout(&self, text: &str, wrap_width: Option<f32>, wrap_on_hyphen: bool) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            match wrap_width {
+                Some(width) => {
+                    let chars: Vec<char> = paragraph.chars().collect();
+                    wrap_paragraph(
+                        &chars,
+                        self.chars_fitting(width).max(1),
+                        wrap_on_hyphen,
+                        &mut lines,
+                    );
+                }
+                None => lines.push(paragraph.to_string()),
+            }
+        }
+        lines
+    }
+
+    /// Paint the lines in the rect, each line justified on its own. Like on a terminal only whole
+    /// lines and characters that fit in the rect are drawn.
+    pub fn paint(
+        &self,
+        ui: &egui::Ui,
+        rect: Rect,
+        lines: &[String],
+        justification: Alignment,
+        style: &TextStyle,
+    ) {
+        let char_size = self.char_size();
+        let pixel = self.pixel_size();
+        let visible_lines = lines
+            .len()
+            .min((rect.height() / char_size.y).floor() as usize);
+        let max_chars = self.chars_fitting(rect.width());
+
+        let block_height = visible_lines as f32 * char_size.y;
+        let top = match justification.vertical {
+            VerticalAlignment::Middle => (rect.center().y - block_height * 0.5).floor(),
+            VerticalAlignment::Bottom => rect.max.y - block_height,
+            _ => rect.min.y,
+        };
+
+        let texture = self.texture(ui);
+        let mut backgrounds = Mesh::default();
+        let mut glyphs = Mesh::with_texture(texture.id());
+        let mut decorations = Mesh::default();
+        for (row, line) in lines.iter().take(visible_lines).enumerate() {
+            let bytes: Vec<u8> = line
+                .chars()
+                .take(max_chars)
+                .map(|c| self.code_page.encode(c))
+                .collect();
+
+            let line_width = bytes.len() as f32 * char_size.x;
+            let left = match justification.horizontal {
+                HorizontalAlignment::Middle => (rect.center().x - line_width * 0.5).floor(),
+                HorizontalAlignment::Right => rect.max.x - line_width,
+                _ => rect.min.x,
+            };
+            let line_rect = Rect::from_min_size(
+                egui::pos2(left, top + row as f32 * char_size.y),
+                egui::vec2(line_width, char_size.y),
+            );
+
+            if let Some(background) = style.background {
+                backgrounds.add_colored_rect(line_rect, background);
+            }
+
+            for (column, byte) in bytes.into_iter().enumerate() {
+                if self.code_page.decode(byte).is_none() {
+                    continue;
+                }
+                let min = line_rect.min + egui::vec2(column as f32 * char_size.x, 0.0);
+                self.add_glyph(&mut glyphs, min, byte, style);
+                if style.bold {
+                    // Bold glyphs are drawn a second time, one pixel to the right
+                    self.add_glyph(&mut glyphs, min + egui::vec2(pixel, 0.0), byte, style);
+                }
+            }
+
+            // Decorations are one pixel of the bitmap thick
+            if style.underlined {
+                let y = line_rect.max.y - pixel;
+                decorations.add_colored_rect(
+                    Rect::from_x_y_ranges(line_rect.x_range(), y..=y + pixel),
+                    style.colour,
+                );
+            }
+            if style.crossed_out {
+                let y = line_rect.min.y + ((char_size.y / pixel / 2.0).floor() * pixel);
+                decorations.add_colored_rect(
+                    Rect::from_x_y_ranges(line_rect.x_range(), y..=y + pixel),
+                    style.colour,
+                );
+            }
+        }
+
+        let painter = ui.painter();
+        for mesh in [backgrounds, glyphs, decorations] {
+            if !mesh.is_empty() {
+                painter.add(egui::Shape::mesh(mesh));
+            }
+        }
+    }
+
+    /// Add a glyph to the mesh with its top left corner at `min`. Italic glyphs are slanted by
+    /// shifting the rows of the bitmap, so they stay sharp.
+    fn add_glyph(&self, mesh: &mut Mesh, min: egui::Pos2, byte: u8, style: &TextStyle) {
+        let char_size = self.char_size();
+        let uv = glyph_uv(byte);
+        if !style.italic {
+            mesh.add_rect_with_uv(Rect::from_min_size(min, char_size), uv, style.colour);
+            return;
+        }
+
+        let pixel = self.pixel_size();
+        let rows = self.bitmap_size().1;
+        for row in 0..rows {
+            let y = row as f32 * pixel;
+            // Rows above the middle of the cell shift to the right, rows below it to the left
+            let shift =
+                ((char_size.y * 0.5 - y - pixel * 0.5) * ITALIC_SLANT / pixel).round() * pixel;
+            let row_uv = Rect::from_min_size(
+                uv.min + egui::vec2(0.0, uv.height() * row as f32 / rows as f32),
+                egui::vec2(uv.width(), uv.height() / rows as f32),
+            );
+            mesh.add_rect_with_uv(
+                Rect::from_min_size(min + egui::vec2(shift, y), egui::vec2(char_size.x, pixel)),
+                row_uv,
+                style.colour,
+            );
+        }
+    }
+
+    /// The size of a single pixel of the bitmap on the screen
+    fn pixel_size(&self) -> f32 {
+        self.char_size().x / self.bitmap_size().0 as f32
+    }
+
+    /// Large fonts are drawn as an integer multiple of a smaller bitmap, like terminals do. The
+    /// largest bitmap that fits the font size is used.
+    fn bitmap_size(&self) -> (usize, usize) {
+        let (width, height) = (self.size.width() as usize, self.size.height() as usize);
+        BITMAP_SIZES
+            .into_iter()
+            .rev()
+            .find(|(w, h)| width % w == 0 && height % h == 0 && width / w == height / h)
+            .unwrap_or(BITMAP_SIZES[0])
+    }
+
+    /// The glyph atlas of this font, created on first use and cached in the egui temp data
+    fn texture(&self, ui: &egui::Ui) -> TextureHandle {
+        let (width, height) = self.bitmap_size();
+        let id = egui::Id::new(("vt_font", self.code_page, width, height));
+        if let Some(texture) = ui.data(|data| data.get_temp::<TextureHandle>(id)) {
+            return texture;
+        }
+
+        let texture = ui.ctx().load_texture(
+            format!("vt_font_{:?}_{}x{}", self.code_page, width, height),
+            load_atlas(self.code_page, (width, height)),
+            TextureOptions::NEAREST,
+        );
+        ui.data_mut(|data| data.insert_temp(id, texture.clone()));
+        texture
+    }
+}
+
+fn wrap_paragraph(
+    paragraph: &[char],
+    max_chars: usize,
+    wrap_on_hyphen: bool,
+    lines: &mut Vec<String>,
+) {
+    let mut start = 0;
+    while paragraph.len() - start > max_chars {
+        // Look one character past the limit, a space there still allows the word before it to fit
+        let window = &paragraph[start..=start + max_chars];
+        let space = window
+            .iter()
+            .rposition(|c| *c == ' ')
+            .filter(|space| *space > 0);
+        // A hyphen stays at the end of the line, so it has to fit itself
+        let hyphen = window[..max_chars]
+            .iter()
+            .rposition(|c| *c == '-')
+            .filter(|_| wrap_on_hyphen);
+
+        match (space, hyphen) {
+            (Some(space), hyphen) if hyphen < Some(space) => {
+                let line: String = window[..space].iter().collect();
+                lines.push(line.trim_end().to_string());
+                start += space;
+            }
+            (_, Some(hyphen)) => {
+                lines.push(window[..=hyphen].iter().collect());
+                start += hyphen + 1;
+            }
+            _ => {
+                lines.push(window[..max_chars].iter().collect());
+                start += max_chars;
+            }
+        }
+        // Wrapped lines don't start with spaces
+        while start < paragraph.len() && paragraph[start] == ' ' {
+            start += 1;
+        }
+    }
+    lines.push(paragraph[start..].iter().collect());
+}
+
+/// The texture coordinates of a byte in the 16 by 16 glyph atlas
+fn glyph_uv(byte: u8) -> Rect {
+    let cell = 1.0 / 16.0;
+    Rect::from_min_size(
+        egui::pos2((byte % 16) as f32 * cell, (byte / 16) as f32 * cell),
+        egui::vec2(cell, cell),
+    )
+}
+
+/// The bundled glyph atlas of a code page for a bitmap size, see `assets/vt_fonts`
+macro_rules! atlas_png {
+    ($name:literal, $size:expr) => {
+        match $size {
+            (6, 8) => &include_bytes!(concat!("../assets/vt_fonts/", $name, "_6x8.png"))[..],
+            (8, 8) => &include_bytes!(concat!("../assets/vt_fonts/", $name, "_8x8.png"))[..],
+            (8, 12) => &include_bytes!(concat!("../assets/vt_fonts/", $name, "_8x12.png"))[..],
+            _ => &include_bytes!(concat!("../assets/vt_fonts/", $name, "_12x16.png"))[..],
+        }
+    };
+}
+
+/// Load the glyph atlas of a code page for one of the `BITMAP_SIZES`, with all 256 glyphs in a
+/// 16 by 16 grid as white pixels on transparent
+fn load_atlas(code_page: CodePage, size: (usize, usize)) -> ColorImage {
+    let png = match code_page {
+        CodePage::Latin1 => atlas_png!("latin1", size),
+        CodePage::Latin9 => atlas_png!("latin9", size),
+        CodePage::Latin2 => atlas_png!("latin2", size),
+        CodePage::Latin4 => atlas_png!("latin4", size),
+        CodePage::Cyrillic => atlas_png!("cyrillic", size),
+        CodePage::Greek => atlas_png!("greek", size),
+    };
+
+    let mut atlas = ColorImage::filled([size.0 * 16, size.1 * 16], Color32::TRANSPARENT);
+    match image::load_from_memory(png) {
+        Ok(bitmap) => {
+            for (x, y, pixel) in bitmap.to_luma8().enumerate_pixels() {
+                if pixel.0[0] > 0 {
+                    atlas[(x as usize, y as usize)] = Color32::WHITE;
+                }
+            }
+        }
+        Err(e) => log::error!("Failed to load the VT font atlas: {}", e),
+    }
+    atlas
+}
+
+/// Upper half (0xA0..=0xFF) of ISO 8859-15 (Latin 9)
+const LATIN_9: [char; 96] = [
+    '\u{00A0}', '\u{00A1}', '\u{00A2}', '\u{00A3}', '\u{20AC}', '\u{00A5}', '\u{0160}', '\u{00A7}',
+    '\u{0161}', '\u{00A9}', '\u{00AA}', '\u{00AB}', '\u{00AC}', '\u{00AD}', '\u{00AE}', '\u{00AF}',
+    '\u{00B0}', '\u{00B1}', '\u{00B2}', '\u{00B3}', '\u{017D}', '\u{00B5}', '\u{00B6}', '\u{00B7}',
+    '\u{017E}', '\u{00B9}', '\u{00BA}', '\u{00BB}', '\u{0152}', '\u{0153}', '\u{0178}', '\u{00BF}',
+    '\u{00C0}', '\u{00C1}', '\u{00C2}', '\u{00C3}', '\u{00C4}', '\u{00C5}', '\u{00C6}', '\u{00C7}',
+    '\u{00C8}', '\u{00C9}', '\u{00CA}', '\u{00CB}', '\u{00CC}', '\u{00CD}', '\u{00CE}', '\u{00CF}',
+    '\u{00D0}', '\u{00D1}', '\u{00D2}', '\u{00D3}', '\u{00D4}', '\u{00D5}', '\u{00D6}', '\u{00D7}',
+    '\u{00D8}', '\u{00D9}', '\u{00DA}', '\u{00DB}', '\u{00DC}', '\u{00DD}', '\u{00DE}', '\u{00DF}',
+    '\u{00E0}', '\u{00E1}', '\u{00E2}', '\u{00E3}', '\u{00E4}', '\u{00E5}', '\u{00E6}', '\u{00E7}',
+    '\u{00E8}', '\u{00E9}', '\u{00EA}', '\u{00EB}', '\u{00EC}', '\u{00ED}', '\u{00EE}', '\u{00EF}',
+    '\u{00F0}', '\u{00F1}', '\u{00F2}', '\u{00F3}', '\u{00F4}', '\u{00F5}', '\u{00F6}', '\u{00F7}',
+    '\u{00F8}', '\u{00F9}', '\u{00FA}', '\u{00FB}', '\u{00FC}', '\u{00FD}', '\u{00FE}', '\u{00FF}',
+];
+
+/// Upper half (0xA0..=0xFF) of ISO 8859-2 (Latin 2)
+const LATIN_2: [char; 96] = [
+    '\u{00A0}', '\u{0104}', '\u{02D8}', '\u{0141}', '\u{00A4}', '\u{013D}', '\u{015A}', '\u{00A7}',
+    '\u{00A8}', '\u{0160}', '\u{015E}', '\u{0164}', '\u{0179}', '\u{00AD}', '\u{017D}', '\u{017B}',
+    '\u{00B0}', '\u{0105}', '\u{02DB}', '\u{0142}', '\u{00B4}', '\u{013E}', '\u{015B}', '\u{02C7}',
+    '\u{00B8}', '\u{0161}', '\u{015F}', '\u{0165}', '\u{017A}', '\u{02DD}', '\u{017E}', '\u{017C}',
+    '\u{0154}', '\u{00C1}', '\u{00C2}', '\u{0102}', '\u{00C4}', '\u{0139}', '\u{0106}', '\u{00C7}',
+    '\u{010C}', '\u{00C9}', '\u{0118}', '\u{00CB}', '\u{011A}', '\u{00CD}', '\u{00CE}', '\u{010E}',
+    '\u{0110}', '\u{0143}', '\u{0147}', '\u{00D3}', '\u{00D4}', '\u{0150}', '\u{00D6}', '\u{00D7}',
+    '\u{0158}', '\u{016E}', '\u{00DA}', '\u{0170}', '\u{00DC}', '\u{00DD}', '\u{0162}', '\u{00DF}',
+    '\u{0155}', '\u{00E1}', '\u{00E2}', '\u{0103}', '\u{00E4}', '\u{013A}', '\u{0107}', '\u{00E7}',
+    '\u{010D}', '\u{00E9}', '\u{0119}', '\u{00EB}', '\u{011B}', '\u{00ED}', '\u{00EE}', '\u{010F}',
+    '\u{0111}', '\u{0144}', '\u{0148}', '\u{00F3}', '\u{00F4}', '\u{0151}', '\u{00F6}', '\u{00F7}',
+    '\u{0159}', '\u{016F}', '\u{00FA}', '\u{0171}', '\u{00FC}', '\u{00FD}', '\u{0163}', '\u{02D9}',
+];
+
+/// Upper half (0xA0..=0xFF) of ISO 8859-4 (Latin 4)
+const LATIN_4: [char; 96] = [
+    '\u{00A0}', '\u{0104}', '\u{0138}', '\u{0156}', '\u{00A4}', '\u{0128}', '\u{013B}', '\u{00A7}',
+    '\u{00A8}', '\u{0160}', '\u{0112}', '\u{0122}', '\u{0166}', '\u{00AD}', '\u{017D}', '\u{00AF}',
+    '\u{00B0}', '\u{0105}', '\u{02DB}', '\u{0157}', '\u{00B4}', '\u{0129}', '\u{013C}', '\u{02C7}',
+    '\u{00B8}', '\u{0161}', '\u{0113}', '\u{0123}', '\u{0167}', '\u{014A}', '\u{017E}', '\u{014B}',
+    '\u{0100}', '\u{00C1}', '\u{00C2}', '\u{00C3}', '\u{00C4}', '\u{00C5}', '\u{00C6}', '\u{012E}',
+    '\u{010C}', '\u{00C9}', '\u{0118}', '\u{00CB}', '\u{0116}', '\u{00CD}', '\u{00CE}', '\u{012A}',
+    '\u{0110}', '\u{0145}', '\u{014C}', '\u{0136}', '\u{00D4}', '\u{00D5}', '\u{00D6}', '\u{00D7}',
+    '\u{00D8}', '\u{0172}', '\u{00DA}', '\u{00DB}', '\u{00DC}', '\u{0168}', '\u{016A}', '\u{00DF}',
+    '\u{0101}', '\u{00E1}', '\u{00E2}', '\u{00E3}', '\u{00E4}', '\u{00E5}', '\u{00E6}', '\u{012F}',
+    '\u{010D}', '\u{00E9}', '\u{0119}', '\u{00EB}', '\u{0117}', '\u{00ED}', '\u{00EE}', '\u{012B}',
+    '\u{0111}', '\u{0146}', '\u{014D}', '\u{0137}', '\u{00F4}', '\u{00F5}', '\u{00F6}', '\u{00F7}',
+    '\u{00F8}', '\u{0173}', '\u{00FA}', '\u{00FB}', '\u{00FC}', '\u{0169}', '\u{016B}', '\u{02D9}',
+];
+
+/// Upper half (0xA0..=0xFF) of ISO 8859-5 (Cyrillic)
+const CYRILLIC: [char; 96] = [
+    '\u{00A0}', '\u{0401}', '\u{0402}', '\u{0403}', '\u{0404}', '\u{0405}', '\u{0406}', '\u{0407}',
+    '\u{0408}', '\u{0409}', '\u{040A}', '\u{040B}', '\u{040C}', '\u{00AD}', '\u{040E}', '\u{040F}',
+    '\u{0410}', '\u{0411}', '\u{0412}', '\u{0413}', '\u{0414}', '\u{0415}', '\u{0416}', '\u{0417}',
+    '\u{0418}', '\u{0419}', '\u{041A}', '\u{041B}', '\u{041C}', '\u{041D}', '\u{041E}', '\u{041F}',
+    '\u{0420}', '\u{0421}', '\u{0422}', '\u{0423}', '\u{0424}', '\u{0425}', '\u{0426}', '\u{0427}',
+    '\u{0428}', '\u{0429}', '\u{042A}', '\u{042B}', '\u{042C}', '\u{042D}', '\u{042E}', '\u{042F}',
+    '\u{0430}', '\u{0431}', '\u{0432}', '\u{0433}', '\u{0434}', '\u{0435}', '\u{0436}', '\u{0437}',
+    '\u{0438}', '\u{0439}', '\u{043A}', '\u{043B}', '\u{043C}', '\u{043D}', '\u{043E}', '\u{043F}',
+    '\u{0440}', '\u{0441}', '\u{0442}', '\u{0443}', '\u{0444}', '\u{0445}', '\u{0446}', '\u{0447}',
+    '\u{0448}', '\u{0449}', '\u{044A}', '\u{044B}', '\u{044C}', '\u{044D}', '\u{044E}', '\u{044F}',
+    '\u{2116}', '\u{0451}', '\u{0452}', '\u{0453}', '\u{0454}', '\u{0455}', '\u{0456}', '\u{0457}',
+    '\u{0458}', '\u{0459}', '\u{045A}', '\u{045B}', '\u{045C}', '\u{00A7}', '\u{045E}', '\u{045F}',
+];
+
+/// Upper half (0xA0..=0xFF) of ISO 8859-7 (Greek)
+const GREEK: [char; 96] = [
+    '\u{00A0}', '\u{2018}', '\u{2019}', '\u{00A3}', '\u{20AC}', '\u{20AF}', '\u{00A6}', '\u{00A7}',
+    '\u{00A8}', '\u{00A9}', '\u{037A}', '\u{00AB}', '\u{00AC}', '\u{00AD}', '\u{0000}', '\u{2015}',
+    '\u{00B0}', '\u{00B1}', '\u{00B2}', '\u{00B3}', '\u{0384}', '\u{0385}', '\u{0386}', '\u{00B7}',
+    '\u{0388}', '\u{0389}', '\u{038A}', '\u{00BB}', '\u{038C}', '\u{00BD}', '\u{038E}', '\u{038F}',
+    '\u{0390}', '\u{0391}', '\u{0392}', '\u{0393}', '\u{0394}', '\u{0395}', '\u{0396}', '\u{0397}',
+    '\u{0398}', '\u{0399}', '\u{039A}', '\u{039B}', '\u{039C}', '\u{039D}', '\u{039E}', '\u{039F}',
+    '\u{03A0}', '\u{03A1}', '\u{0000}', '\u{03A3}', '\u{03A4}', '\u{03A5}', '\u{03A6}', '\u{03A7}',
+    '\u{03A8}', '\u{03A9}', '\u{03AA}', '\u{03AB}', '\u{03AC}', '\u{03AD}', '\u{03AE}', '\u{03AF}',
+    '\u{03B0}', '\u{03B1}', '\u{03B2}', '\u{03B3}', '\u{03B4}', '\u{03B5}', '\u{03B6}', '\u{03B7}',
+    '\u{03B8}', '\u{03B9}', '\u{03BA}', '\u{03BB}', '\u{03BC}', '\u{03BD}', '\u{03BE}', '\u{03BF}',
+    '\u{03C0}', '\u{03C1}', '\u{03C2}', '\u{03C3}', '\u{03C4}', '\u{03C5}', '\u{03C6}', '\u{03C7}',
+    '\u{03C8}', '\u{03C9}', '\u{03CA}', '\u{03CB}', '\u{03CC}', '\u{03CD}', '\u{03CE}', '\u{0000}',
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE_PAGES: [CodePage; 6] = [
+        CodePage::Latin1,
+        CodePage::Latin9,
+        CodePage::Latin2,
+        CodePage::Latin4,
+        CodePage::Cyrillic,
+        CodePage::Greek,
+    ];
+
+    fn wrap(text: &str, max_chars: usize, wrap_on_hyphen: bool) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut lines = Vec::new();
+        wrap_paragraph(&chars, max_chars, wrap_on_hyphen, &mut lines);
+        lines
+    }
+
+    #[test]
+    fn wrap_on_spaces() {
+        assert_eq!(wrap("one two three", 7, false), vec!["one two", "three"]);
+        // A space just past the limit still lets the word before it fit
+        assert_eq!(wrap("abc def", 3, false), vec!["abc", "def"]);
+        // Wrapped lines don't start with spaces
+        assert_eq!(wrap("abc   def", 4, false), vec!["abc", "def"]);
+        assert_eq!(wrap("fits", 4, false), vec!["fits"]);
+        assert_eq!(wrap("", 4, false), vec![""]);
+    }
+
+    #[test]
+    fn wrap_long_words() {
+        assert_eq!(wrap("abcdefgh", 3, false), vec!["abc", "def", "gh"]);
+        assert_eq!(wrap("one-two", 5, false), vec!["one-t", "wo"]);
+        assert_eq!(wrap("one-two", 5, true), vec!["one-", "two"]);
+        // A space after the hyphen is preferred
+        assert_eq!(wrap("a-b cd", 4, true), vec!["a-b", "cd"]);
+    }
+
+    #[test]
+    fn decode_bytes() {
+        assert_eq!(CodePage::Latin1.decode(b'A'), Some('A'));
+        assert_eq!(CodePage::Latin1.decode(0xE9), Some('é'));
+        assert_eq!(CodePage::Latin9.decode(0xA4), Some('€'));
+        assert_eq!(CodePage::Cyrillic.decode(0xB6), Some('Ж'));
+        assert_eq!(CodePage::Greek.decode(0xE1), Some('α'));
+        // Control codes and unassigned bytes have no character
+        assert_eq!(CodePage::Latin1.decode(0x0A), None);
+        assert_eq!(CodePage::Latin1.decode(0x85), None);
+        assert_eq!(CodePage::Greek.decode(0xAE), None);
+    }
+
+    #[test]
+    fn encode_chars() {
+        assert_eq!(CodePage::Latin9.encode('€'), 0xA4);
+        assert_eq!(CodePage::Cyrillic.encode('Ж'), 0xB6);
+        assert_eq!(CodePage::Latin1.encode('é'), 0xE9);
+        // Characters outside of the code page are shown as a question mark
+        assert_eq!(CodePage::Latin1.encode('€'), b'?');
+        assert_eq!(CodePage::Greek.encode('Ж'), b'?');
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        for code_page in CODE_PAGES {
+            for byte in 0x20..=0xFF {
+                if let Some(c) = code_page.decode(byte) {
+                    assert_eq!(code_page.encode(c), byte, "{:?} {:#04X}", code_page, byte);
+                }
+            }
+        }
+    }
+}